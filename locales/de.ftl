@@ -0,0 +1,191 @@
+# Bot texts, one catalog per language with the same ids.
+
+language-name = Deutsch
+
+## Commands
+
+command-settings = Den Buy-Bot und seine Sprache einrichten
+command-start = Die Startnachricht anzeigen
+command-tokens = Die verfolgten Tokens anzeigen und verwalten
+
+## Group chat
+
+settings-unsupported-chat = Der Befehl /settings wird in dieser Art von Chat nicht unterstützt.
+settings-no-user = Die Benutzerinformationen konnten nicht verarbeitet werden
+settings-private-chat = Dieser Bot zeigt die Käufe der Token an, die deine Gruppe verfolgt. Tippe /help für weitere Informationen
+settings-open = @{ $username }, klicke zum Einrichten auf den Button unten und starte einen privaten Chat mit mir, falls noch nicht geschehen.
+settings-open-button = Einstellungen öffnen
+settings-link-invalid = ⚠️ Dieser Einstellungslink ist ungültig oder abgelaufen. Bitte führe /settings erneut in deiner Gruppe aus.
+not-admin = ⛔ Nur Administratoren der Gruppe können ihre Einstellungen ändern.
+anonymous-admin = Du schreibst anonym als Gruppe. Tippe auf den Button unten, damit ich prüfen kann, ob du Administrator bist.
+anonymous-admin-button = Ich bin Admin
+tracking-started = Neue Käufe von { $token } auf { $chain } werden verfolgt...
+
+## Private chat
+
+choose-chain = Bitte wähle die Chain des Tokens, das verfolgt werden soll
+no-session = ⚠️ Keine Gruppe ausgewählt. Bitte führe zuerst /settings in der Gruppe aus, die du einrichten möchtest.
+choose-media-type = Bitte wähle die Art des Mediums.
+choose-language = Bitte wähle die Sprache der Einstellungen und der Meldungen.
+media-photo = Foto
+media-video = Video
+token-deleted = 🗑 { $token } wird in dieser Gruppe nicht mehr verfolgt.
+token-not-found = Der Token { $token } wurde nicht gefunden.
+token-no-pairs = ⚠️ Für { $token } wurde noch kein DEX-Pair gefunden. Käufe und Verkäufe sehen daher wie einfache Transfers aus und werden nicht gemeldet. Der Bot sucht weiter und meldet sie, sobald ein Pair gelistet ist.
+invalid-token-address = ❌ Die Token-Adresse ist ungültig. Versuche es erneut
+template-editor =
+    Aktuelle Vorlage für Meldungen:
+
+    { $template }
+
+    Platzhalter:
+    { $placeholders }
+template-reset-word = standard
+template-invalid = ❌ { $error }
+
+## Tokens
+
+tokens-list =
+    In dieser Gruppe verfolgte Tokens:
+    { $tokens }
+tokens-none = Diese Gruppe verfolgt noch keinen Token.
+editing-token = Einstellungen von { $token }:
+remove-token-confirm = { $token } in dieser Gruppe nicht mehr verfolgen? Damit werden seine Einstellungen für alle Admins gelöscht.
+
+## Prompts, answered by the next message in the chat
+
+prompt-token-address = Sende die Token-Adresse (0x...)
+prompt-min-buy-amount = Sende den Mindestkauf in USD
+prompt-buy-step = Sende die Kaufstufe in USD, ein Emoji pro Stufe
+prompt-emoji = Sende das Kauf-Emoji
+prompt-tg-link = Sende den Telegram-Link
+prompt-website-link = Sende den Link zur Website
+prompt-twitter-link = Sende den X-(Twitter-)Link
+prompt-min-sell-amount = Sende den Mindestverkauf in USD
+prompt-sell-emoji = Sende das Verkaufs-Emoji
+prompt-buy-template = Sende die neue Vorlage in Telegram-HTML oder "{ template-reset-word }" für das Standardlayout.
+prompt-photo = Sende das Kauf-Foto
+prompt-video = Sende das Kauf-Video
+prompt-sell-photo = Sende das Verkaufs-Foto
+prompt-sell-video = Sende das Verkaufs-Video
+
+## Replies
+
+saved-token-address = 🎉 Token-Adresse gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+saved-min-buy-amount = 🎉 Mindestkauf gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-min-buy-amount = ❌ Der Mindestkauf ist ungültig. Bitte versuche es erneut.
+saved-buy-step = 🎉 Kaufstufe gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-buy-step = ❌ Die Kaufstufe ist ungültig. Bitte versuche es erneut.
+saved-min-sell-amount = 🎉 Mindestverkauf gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-min-sell-amount = ❌ Der Mindestverkauf ist ungültig. Bitte versuche es erneut.
+saved-emoji = 🎉 Emoji gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-emoji = ❌ Das Emoji ist ungültig. Bitte versuche es erneut.
+saved-sell-emoji = 🎉 Verkaufs-Emoji gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-sell-emoji = ❌ Das Verkaufs-Emoji ist ungültig. Bitte versuche es erneut.
+saved-tg-link = 🎉 Telegram-Link gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-tg-link = ❌ Der Telegram-Link ist ungültig. Bitte versuche es erneut.
+saved-website-link = 🎉 Website-Link gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-website-link = ❌ Der Website-Link ist ungültig. Bitte versuche es erneut.
+saved-twitter-link = 🎉 X-Link gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-twitter-link = ❌ Der X-Link ist ungültig. Bitte versuche es erneut.
+saved-buy-template = 🎉 Vorlage gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-buy-template = ❌ Die Vorlage ist ungültig. Bitte versuche es erneut.
+reset-buy-template = 🎉 Standardvorlage wiederhergestellt. Jetzt kannst du die anderen Einstellungen anpassen:
+saved-photo = 🎉 Foto gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-photo = ❌ Ungültiges Foto. Bitte versuche es erneut.
+saved-video = 🎉 Video gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+invalid-video = ❌ Ungültiges Video. Bitte versuche es erneut.
+saved-media-toggle = 🎉 Medien-Einstellung gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+saved-sell-alerts = 🎉 Verkaufsmeldungen gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+saved-language = 🎉 Sprache gespeichert. Jetzt kannst du die anderen Einstellungen anpassen:
+
+## Settings keyboard
+
+value-on = an
+value-off = aus
+button-min-buy = Mindestkauf ändern: { $value }
+button-buy-step = Kaufstufe ändern: { $value }
+button-emoji = Emoji ändern: { $value }
+button-media-toggle = Medien an/aus: { $value }
+button-add-media = Medium hinzufügen: { $value }
+button-tg-link = Telegram-Link ändern: { $value }
+button-twitter-link = X-Link ändern: { $value }
+button-website-link = Website-Link ändern: { $value }
+button-sell-alerts = Verkaufsmeldungen an/aus: { $value }
+button-min-sell = Mindestverkauf ändern: { $value }
+button-sell-emoji = Verkaufs-Emoji ändern: { $value }
+button-add-sell-media = Verkaufs-Medium hinzufügen: { $value }
+button-buy-template = Vorlage bearbeiten: { $value }
+template-custom = eigene
+template-default = Standard
+button-language = Sprache: { $value }
+button-delete-token = Token löschen
+button-tokens = ⬅️ Alle Tokens
+button-edit-token = ✏️ { $token } ({ $chain })
+button-remove-token = 🗑 Entfernen
+button-add-token = ➕ Token hinzufügen
+button-confirm-remove = Ja, entfernen
+button-cancel = Abbrechen
+button-back = ⬅️ Zurück
+button-menu-thresholds = 📊 Schwellenwerte ›
+button-menu-media = 🖼 Medien ›
+button-menu-links = 🔗 Links ›
+button-failed = ⚠️ Etwas ist schiefgelaufen, bitte versuche es erneut.
+
+## Alerts
+
+alert-spent = Ausgegeben
+alert-got = Erhalten
+alert-gas = Gas
+alert-dex = Dex
+alert-book-trending = Trending buchen
+alert-ads = Werbung
+alert-price = Preis
+alert-marketcap = Marktkapitalisierung
+alert-sold = Verkauft
+alert-value = Wert
+alert-chart = Chart
+alert-website = Website
+
+## Template placeholders
+
+placeholder-emoji-bar = ein Emoji pro Kaufstufe in USD
+placeholder-spent-usd = Wert in USD, z. B. 3K
+placeholder-spent-native = nativer Betrag in Klammern, z. B. " (1.250 APE)", falls bekannt
+placeholder-got-amount = gekaufte Token
+placeholder-amount = gekaufte oder verkaufte Token
+placeholder-value-usd = Wert des Trades in USD
+placeholder-symbol = Token-Symbol
+placeholder-gas = bezahltes Gas, z. B. 0,0036 APE
+placeholder-price = Token-Preis in USD
+placeholder-mcap = Marktkapitalisierung in USD
+placeholder-buyer = Adresse des Käufers, gekürzt
+placeholder-tx-link = URL der Transaktion
+placeholder-chart-link = URL des Charts
+placeholder-dex-link = URL der DEX
+placeholder-dex-name = Name der DEX
+placeholder-trending-link = der Trending-Bot der Chain, falls es einen gibt
+placeholder-ads-link = der Werbe-Bot der Chain, falls es einen gibt
+placeholder-tg-link = der Telegram-Link der Gruppe
+placeholder-twitter-link = der X-Link der Gruppe
+placeholder-website-link = die Website der Gruppe
+
+## Template errors
+
+template-error-empty = die Vorlage ist leer
+template-error-too-long = die Vorlage hat { $chars } Zeichen, höchstens { $max } passen in eine Meldung
+template-error-unknown-placeholder = unbekannter Platzhalter { $placeholder }
+template-error-unclosed-placeholder = eine {"{"} wird nie geschlossen, schreibe {"{{"} für eine Klammer
+template-error-stray-closing-brace = eine {"}"} hat keine {"{"}, schreibe {"}}"} für eine Klammer
+template-error-not-a-link = { $placeholder } ist kein Link
+template-error-whole-link = { $placeholder } muss der ganze Link sein, z. B. <a href="{ $placeholder }">
+template-error-unclosed-link = <a> wird nie geschlossen
+markup-error-ampersand = schreibe &amp; für ein &-Zeichen
+markup-error-greater-than = schreibe &gt; für ein >-Zeichen
+markup-error-less-than = schreibe &lt; für ein <-Zeichen
+markup-error-unclosed-tag = <{ $tag }> wird nie geschlossen
+markup-error-mismatched-tag = </{ $closing }> schließt <{ $open }>
+markup-error-unopened-tag = </{ $tag }> wurde nie geöffnet
+markup-error-unsupported-tag = Telegram unterstützt <{ $tag }> nicht
+markup-error-link-syntax = Links müssen als <a href="..."> geschrieben werden
+markup-error-invalid-link = { $href } ist kein gültiger Link
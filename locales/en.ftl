@@ -0,0 +1,191 @@
+# Bot texts, one catalog per language with the same ids.
+
+language-name = English
+
+## Commands
+
+command-settings = Configure the buy bot and its language
+command-start = Show the start message
+command-tokens = List and manage the tracked tokens
+
+## Group chat
+
+settings-unsupported-chat = /settings command is not supported in this chat type.
+settings-no-user = Could not process user information
+settings-private-chat = This bot posts the buys of the tokens your group tracks. Type /help for more information
+settings-open = @{ $username }, to configure settings, please click the button below and then start a private chat with me if you haven't already.
+settings-open-button = Configure Settings
+settings-link-invalid = ⚠️ This settings link is invalid or has expired. Please run /settings in your group again.
+not-admin = ⛔ Only administrators of the group can change its settings.
+anonymous-admin = You are posting anonymously as the group. Tap the button below so I can check that you are an administrator.
+anonymous-admin-button = I am an admin
+tracking-started = Catching new buy transactions of { $token } on { $chain }...
+
+## Private chat
+
+choose-chain = Please choose the chain of the token you want to track
+no-session = ⚠️ No group selected. Please run /settings in the group you want to configure first.
+choose-media-type = Please choose the type of media.
+choose-language = Please choose the language of the settings and the alerts.
+media-photo = Photo
+media-video = Video
+token-deleted = 🗑 { $token } is no longer tracked in this group.
+token-not-found = The token { $token } is not found.
+token-no-pairs = ⚠️ No DEX pair was found for { $token } yet, so its buys and sells look like plain transfers and are not alerted. The bot keeps looking and starts alerting once a pair is listed.
+invalid-token-address = ❌ Token address is not valid. Try again
+template-editor =
+    Current alert template:
+
+    { $template }
+
+    Placeholders:
+    { $placeholders }
+template-reset-word = default
+template-invalid = ❌ { $error }
+
+## Tokens
+
+tokens-list =
+    Tokens tracked in this group:
+    { $tokens }
+tokens-none = This group does not track any token yet.
+editing-token = Settings of { $token }:
+remove-token-confirm = Stop tracking { $token } in this group? This removes its settings for every admin.
+
+## Prompts, answered by the next message in the chat
+
+prompt-token-address = Send the token address (0x...)
+prompt-min-buy-amount = Send the minimum buy in USD
+prompt-buy-step = Send the buy step in USD, one emoji per step
+prompt-emoji = Send the buy emoji
+prompt-tg-link = Send the Telegram link
+prompt-website-link = Send the website link
+prompt-twitter-link = Send the X (Twitter) link
+prompt-min-sell-amount = Send the minimum sell in USD
+prompt-sell-emoji = Send the sell emoji
+prompt-buy-template = Send the new template in Telegram HTML, or "{ template-reset-word }" for the default layout.
+prompt-photo = Send the buy photo
+prompt-video = Send the buy video
+prompt-sell-photo = Send the sell photo
+prompt-sell-video = Send the sell video
+
+## Replies
+
+saved-token-address = 🎉 Token address saved. Now you can adjust the other settings:
+saved-min-buy-amount = 🎉 Min buy amount saved. Now you can adjust the other settings:
+invalid-min-buy-amount = ❌ Min buy amount is not valid. Please try again.
+saved-buy-step = 🎉 Buy step saved. Now you can adjust the other settings:
+invalid-buy-step = ❌ Buy step is not valid. Please try again.
+saved-min-sell-amount = 🎉 Min sell amount saved. Now you can adjust the other settings:
+invalid-min-sell-amount = ❌ Min sell amount is not valid. Please try again.
+saved-emoji = 🎉 Emoji saved. Now you can adjust the other settings:
+invalid-emoji = ❌ Emoji is not valid. Please try again.
+saved-sell-emoji = 🎉 Sell emoji saved. Now you can adjust the other settings:
+invalid-sell-emoji = ❌ Sell emoji is not valid. Please try again.
+saved-tg-link = 🎉 Tg link saved. Now you can adjust the other settings:
+invalid-tg-link = ❌ Tg link is not valid. Please try again.
+saved-website-link = 🎉 Website link saved. Now you can adjust the other settings:
+invalid-website-link = ❌ Website link is not valid. Please try again.
+saved-twitter-link = 🎉 Twitter link saved. Now you can adjust the other settings:
+invalid-twitter-link = ❌ Twitter link is not valid. Please try again.
+saved-buy-template = 🎉 Alert template saved. Now you can adjust the other settings:
+invalid-buy-template = ❌ Alert template is not valid. Please try again.
+reset-buy-template = 🎉 Default alert template restored. Now you can adjust the other settings:
+saved-photo = 🎉 Photo saved. Now you can adjust the other settings:
+invalid-photo = ❌ Invalid photo style. Please try again.
+saved-video = 🎉 Video saved. Now you can adjust the other settings:
+invalid-video = ❌ Invalid video style. Please try again.
+saved-media-toggle = 🎉 Media toggle option is saved. Now you can adjust the other settings:
+saved-sell-alerts = 🎉 Sell alerts option is saved. Now you can adjust the other settings:
+saved-language = 🎉 Language saved. Now you can adjust the other settings:
+
+## Settings keyboard
+
+value-on = on
+value-off = off
+button-min-buy = Change minBuy: { $value }
+button-buy-step = Change step: { $value }
+button-emoji = Change Emoji: { $value }
+button-media-toggle = Enable/Disable media: { $value }
+button-add-media = Add Media: { $value }
+button-tg-link = Change Tg Link: { $value }
+button-twitter-link = Change Twitter Link: { $value }
+button-website-link = Change Website Link: { $value }
+button-sell-alerts = Enable/Disable sell alerts: { $value }
+button-min-sell = Change minSell: { $value }
+button-sell-emoji = Change sell Emoji: { $value }
+button-add-sell-media = Add sell Media: { $value }
+button-buy-template = Edit alert template: { $value }
+template-custom = custom
+template-default = default
+button-language = Language: { $value }
+button-delete-token = Delete Token
+button-tokens = ⬅️ All tokens
+button-edit-token = ✏️ { $token } ({ $chain })
+button-remove-token = 🗑 Remove
+button-add-token = ➕ Add token
+button-confirm-remove = Yes, remove
+button-cancel = Cancel
+button-back = ⬅️ Back
+button-menu-thresholds = 📊 Thresholds ›
+button-menu-media = 🖼 Media ›
+button-menu-links = 🔗 Links ›
+button-failed = ⚠️ Something went wrong, please try again.
+
+## Alerts
+
+alert-spent = Spent
+alert-got = Got
+alert-gas = Gas
+alert-dex = Dex
+alert-book-trending = Book Trending
+alert-ads = ADS
+alert-price = Price
+alert-marketcap = Marketcap
+alert-sold = Sold
+alert-value = Value
+alert-chart = Chart
+alert-website = Website
+
+## Template placeholders
+
+placeholder-emoji-bar = one emoji per buy step of USD value
+placeholder-spent-usd = USD value, e.g. 3K
+placeholder-spent-native = native amount in brackets, e.g. " (1,250 APE)", if known
+placeholder-got-amount = tokens bought
+placeholder-amount = tokens bought or sold
+placeholder-value-usd = USD value of the trade
+placeholder-symbol = token symbol
+placeholder-gas = gas paid, e.g. 0.0036 APE
+placeholder-price = token price in USD
+placeholder-mcap = market cap in USD
+placeholder-buyer = buyer address, shortened
+placeholder-tx-link = transaction URL
+placeholder-chart-link = chart URL
+placeholder-dex-link = DEX URL
+placeholder-dex-name = DEX name
+placeholder-trending-link = the chain's trending bot, if it has one
+placeholder-ads-link = the chain's ads bot, if it has one
+placeholder-tg-link = the group's Telegram link
+placeholder-twitter-link = the group's X link
+placeholder-website-link = the group's website
+
+## Template errors
+
+template-error-empty = the template is empty
+template-error-too-long = the template has { $chars } characters, at most { $max } fit an alert
+template-error-unknown-placeholder = unknown placeholder { $placeholder }
+template-error-unclosed-placeholder = a {"{"} is never closed, write {"{{"} for a brace
+template-error-stray-closing-brace = a {"}"} has no {"{"}, write {"}}"} for a brace
+template-error-not-a-link = { $placeholder } is not a link
+template-error-whole-link = { $placeholder } must be the whole link, e.g. <a href="{ $placeholder }">
+template-error-unclosed-link = <a> is never closed
+markup-error-ampersand = write &amp; for a & sign
+markup-error-greater-than = write &gt; for a > sign
+markup-error-less-than = write &lt; for a < sign
+markup-error-unclosed-tag = <{ $tag }> is never closed
+markup-error-mismatched-tag = </{ $closing }> closes <{ $open }>
+markup-error-unopened-tag = </{ $tag }> was never opened
+markup-error-unsupported-tag = Telegram does not support <{ $tag }>
+markup-error-link-syntax = links must be written <a href="...">
+markup-error-invalid-link = { $href } is not a valid link
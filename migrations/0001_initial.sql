@@ -0,0 +1,26 @@
+CREATE TABLE IF NOT EXISTS user_info (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    user_id BIGINT NOT NULL UNIQUE,
+    username VARCHAR(255),
+    first_name VARCHAR(255),
+    last_name VARCHAR(255),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS setting_opts (
+    id VARCHAR(255) PRIMARY KEY,
+    user_id VARCHAR(255) NOT NULL,
+    group_chat_id VARCHAR(255) NOT NULL,
+    token_address VARCHAR(42) NOT NULL,
+    min_buy_amount DOUBLE NOT NULL,
+    buy_step INT NOT NULL,
+    emoji VARCHAR(10) NOT NULL,
+    media_toggle BOOLEAN NOT NULL,
+    media_file_id VARCHAR(255),
+    media_type VARCHAR(10),
+    tg_link VARCHAR(255),
+    website_link VARCHAR(255),
+    twitter_link VARCHAR(255),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY unique_id (id)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
@@ -0,0 +1,5 @@
+ALTER TABLE setting_opts ADD COLUMN sell_alerts BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE setting_opts ADD COLUMN min_sell_amount DOUBLE NOT NULL DEFAULT 0;
+ALTER TABLE setting_opts ADD COLUMN sell_emoji VARCHAR(10) NOT NULL DEFAULT '🔴';
+ALTER TABLE setting_opts ADD COLUMN sell_media_file_id VARCHAR(255);
+ALTER TABLE setting_opts ADD COLUMN sell_media_type VARCHAR(10);
@@ -0,0 +1 @@
+ALTER TABLE setting_opts ADD COLUMN chain_id VARCHAR(32) NOT NULL DEFAULT 'apechain' AFTER group_chat_id;
@@ -0,0 +1 @@
+ALTER TABLE setting_opts ADD COLUMN buy_template TEXT NULL;
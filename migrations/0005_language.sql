@@ -0,0 +1 @@
+ALTER TABLE setting_opts ADD COLUMN language VARCHAR(8) NOT NULL DEFAULT 'en';
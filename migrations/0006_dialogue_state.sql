@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS dialogue_state (
+    chat_id BIGINT PRIMARY KEY,
+    state TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
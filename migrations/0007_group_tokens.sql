@@ -0,0 +1,10 @@
+-- Tokens belong to their group instead of the admin who added them. When
+-- several admins tracked the same token, the row added last wins.
+DELETE older FROM setting_opts older
+JOIN setting_opts newer
+  ON newer.group_chat_id = older.group_chat_id
+ AND newer.token_address = older.token_address
+ AND (newer.created_at > older.created_at
+      OR (newer.created_at = older.created_at AND newer.id > older.id));
+UPDATE setting_opts SET id = CONCAT(group_chat_id, '/', token_address);
+CREATE INDEX idx_setting_opts_group ON setting_opts (group_chat_id);
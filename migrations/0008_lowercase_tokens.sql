@@ -0,0 +1,5 @@
+-- Token addresses are kept lowercased, the way the watchers key them. The
+-- key is case-insensitive, so no two rows differ only in case.
+UPDATE setting_opts
+SET token_address = LOWER(token_address),
+    id = CONCAT(group_chat_id, '/', LOWER(token_address));
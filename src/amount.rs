@@ -0,0 +1,168 @@
+use primitive_types::U256;
+use std::fmt;
+
+/// Largest power of ten that fits in a U256.
+const MAX_DECIMALS: u8 = 77;
+
+/// Native coins have 18 decimals on every supported chain.
+pub const NATIVE_DECIMALS: u8 = 18;
+
+/// An exact token amount: a raw on-chain integer and the token's decimals.
+///
+/// Explorer values such as `1000000000000000000000000000` overflow the 53 bits
+/// of an `f64` mantissa. Amounts stay exact until they are multiplied by a
+/// (floating point) USD price.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TokenAmount {
+    raw: U256,
+    decimals: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmountError(String);
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for AmountError {}
+
+impl From<&str> for AmountError {
+    fn from(message: &str) -> Self {
+        Self(message.to_string())
+    }
+}
+
+impl TokenAmount {
+    pub fn new(raw: U256, decimals: u8) -> Result<Self, AmountError> {
+        if decimals > MAX_DECIMALS {
+            return Err(AmountError(format!(
+                "{} decimals is more than a 256-bit amount can hold",
+                decimals
+            )));
+        }
+        Ok(Self { raw, decimals })
+    }
+
+    /// Parses the raw integer and decimals strings the explorer returns.
+    pub fn parse(raw: &str, decimals: &str) -> Result<Self, AmountError> {
+        let decimals = decimals
+            .trim()
+            .parse::<u8>()
+            .map_err(|e| AmountError(format!("invalid decimals {:?}: {}", decimals, e)))?;
+        if raw.trim().is_empty() {
+            return Err(AmountError("empty amount".to_string()));
+        }
+        let raw = U256::from_dec_str(raw.trim())
+            .map_err(|e| AmountError(format!("invalid amount {:?}: {:?}", raw, e)))?;
+        Self::new(raw, decimals)
+    }
+
+    /// Parses a native coin amount given in wei.
+    pub fn native(wei: &str) -> Result<Self, AmountError> {
+        Self::parse(wei, &NATIVE_DECIMALS.to_string())
+    }
+
+    pub fn raw(&self) -> U256 {
+        self.raw
+    }
+
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.raw.is_zero()
+    }
+
+    /// Adds two amounts of the same token, or `None` on a decimals mismatch
+    /// or overflow.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        if self.decimals != other.decimals {
+            return None;
+        }
+        Some(Self {
+            raw: self.raw.checked_add(other.raw)?,
+            decimals: self.decimals,
+        })
+    }
+
+    /// Nearest `f64`, for math against float prices.
+    pub fn to_f64(&self) -> f64 {
+        // Parsing the exact decimal string rounds once, to the nearest f64
+        self.to_string().parse().unwrap_or(f64::MAX)
+    }
+
+    /// USD value at `price` per whole token.
+    pub fn value_at(&self, price: f64) -> f64 {
+        self.to_f64() * price
+    }
+}
+
+/// Exact decimal representation without trailing zeros, e.g. `1500.25`.
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = U256::exp10(self.decimals as usize);
+        let whole = self.raw / unit;
+        let fraction = self.raw % unit;
+        if fraction.is_zero() {
+            return write!(f, "{}", whole);
+        }
+        let fraction = format!("{:0>width$}", fraction, width = self.decimals as usize);
+        write!(f, "{}.{}", whole, fraction.trim_end_matches('0'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_large_supplies_exact() {
+        let supply = TokenAmount::parse("1000000000000000000000000001", "18").unwrap();
+        assert_eq!(supply.to_string(), "1000000000.000000000000000001");
+
+        let max = TokenAmount::parse(&U256::MAX.to_string(), "0").unwrap();
+        assert_eq!(max.to_string(), U256::MAX.to_string());
+    }
+
+    #[test]
+    fn scales_by_decimals() {
+        let amount = TokenAmount::parse("1500250000", "6").unwrap();
+        assert_eq!(amount.to_string(), "1500.25");
+        assert_eq!(amount.to_f64(), 1500.25);
+        assert_eq!(amount.value_at(2.0), 3000.5);
+        assert_eq!(
+            TokenAmount::parse("5", "18").unwrap().to_string(),
+            "0.000000000000000005"
+        );
+        assert_eq!(TokenAmount::parse("0", "18").unwrap().to_string(), "0");
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        assert!(TokenAmount::parse("", "18").is_err());
+        assert!(TokenAmount::parse("12.5", "18").is_err());
+        assert!(TokenAmount::parse("-1", "18").is_err());
+        assert!(TokenAmount::parse("1", "").is_err());
+        assert!(TokenAmount::parse("1", "78").is_err());
+        // One more than U256::MAX
+        assert!(TokenAmount::parse(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936",
+            "0"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn adds_amounts_of_the_same_token() {
+        let a = TokenAmount::parse("1", "18").unwrap();
+        let b = TokenAmount::parse("2", "18").unwrap();
+        assert_eq!(a.checked_add(b).unwrap().raw(), U256::from(3));
+        assert!(a
+            .checked_add(TokenAmount::parse("2", "6").unwrap())
+            .is_none());
+    }
+}
@@ -0,0 +1,102 @@
+/// Everything the bot needs to know about an EVM chain: where to read
+/// transfers, which id the price and pair providers use for it, and the links
+/// put into alerts.
+///
+/// The explorer must expose the Blockscout `/api/v2` API.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Chain {
+    /// Stored in `SettingOpts::chain_id`.
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Blockscout API base, without a trailing slash.
+    pub explorer_api: &'static str,
+    pub debank_chain_id: &'static str,
+    /// `None` if Birdeye does not cover the chain.
+    pub birdeye_chain_id: Option<&'static str>,
+    pub dexscreener_chain_id: &'static str,
+    pub native_symbol: &'static str,
+    /// Wrapped native token (WAPE, WETH), lowercased.
+    pub wrapped_native: &'static str,
+    /// Link patterns. `{tx}` is replaced by the transaction hash and `{token}`
+    /// by the token address.
+    pub tx_url: &'static str,
+    pub chart_url: &'static str,
+    pub dex_url: &'static str,
+    pub dex_name: &'static str,
+    /// Bots selling trending spots and ads on the chain, linked from the buy
+    /// alert. `None` leaves the link out.
+    pub trending_url: Option<&'static str>,
+    pub ads_url: Option<&'static str>,
+}
+
+pub const DEFAULT_CHAIN_ID: &str = "apechain";
+
+pub const CHAINS: &[Chain] = &[
+    Chain {
+        id: "apechain",
+        name: "ApeChain",
+        explorer_api: "https://apechain.calderaexplorer.xyz/api/v2",
+        debank_chain_id: "ape",
+        birdeye_chain_id: None,
+        dexscreener_chain_id: "apechain",
+        native_symbol: "APE",
+        wrapped_native: "0x48b62137edfa95a428d35c09e44256a739f6b557",
+        tx_url: "https://apescan.io/tx/{tx}",
+        chart_url: "https://dexscreener.com/apechain/{token}",
+        dex_url: "https://ape.express/explore/{token}?",
+        dex_name: "Ape_Express",
+        trending_url: Some("https://t.me/Apechain_Trending_Bot"),
+        ads_url: Some("https://t.me/ApechainAds_Bot"),
+    },
+    Chain {
+        id: "ethereum",
+        name: "Ethereum",
+        explorer_api: "https://eth.blockscout.com/api/v2",
+        debank_chain_id: "eth",
+        birdeye_chain_id: Some("ethereum"),
+        dexscreener_chain_id: "ethereum",
+        native_symbol: "ETH",
+        wrapped_native: "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+        tx_url: "https://etherscan.io/tx/{tx}",
+        chart_url: "https://dexscreener.com/ethereum/{token}",
+        dex_url: "https://app.uniswap.org/swap?chain=mainnet&outputCurrency={token}",
+        dex_name: "Uniswap",
+        trending_url: None,
+        ads_url: None,
+    },
+    Chain {
+        id: "base",
+        name: "Base",
+        explorer_api: "https://base.blockscout.com/api/v2",
+        debank_chain_id: "base",
+        birdeye_chain_id: Some("base"),
+        dexscreener_chain_id: "base",
+        native_symbol: "ETH",
+        wrapped_native: "0x4200000000000000000000000000000000000006",
+        tx_url: "https://basescan.org/tx/{tx}",
+        chart_url: "https://dexscreener.com/base/{token}",
+        dex_url: "https://app.uniswap.org/swap?chain=base&outputCurrency={token}",
+        dex_name: "Uniswap",
+        trending_url: None,
+        ads_url: None,
+    },
+];
+
+/// Looks a chain up by its id.
+pub fn find_chain(id: &str) -> Option<&'static Chain> {
+    CHAINS.iter().find(|chain| chain.id == id)
+}
+
+impl Chain {
+    pub fn tx_url(&self, tx_hash: &str) -> String {
+        self.tx_url.replace("{tx}", tx_hash)
+    }
+
+    pub fn chart_url(&self, token_address: &str) -> String {
+        self.chart_url.replace("{token}", token_address)
+    }
+
+    pub fn dex_url(&self, token_address: &str) -> String {
+        self.dex_url.replace("{token}", token_address)
+    }
+}
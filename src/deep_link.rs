@@ -0,0 +1,192 @@
+//! Signed `/start` payloads that tell the private settings chat which group
+//! the admin came from, e.g. `cfg_-1001234567890_1760000600_<signature>`.
+//!
+//! The signature covers the group, the expiry and the admin who ran
+//! `/settings`, so a link posted in the group is useless to anyone else and
+//! after it expires.
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::config::DeepLinkConfig;
+
+const PREFIX: &str = "cfg";
+
+/// Hex digits of the signature kept in the payload. Telegram allows 64
+/// characters in a start payload.
+const SIGNATURE_CHARS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeepLinkError {
+    Malformed,
+    BadSignature,
+    Expired,
+}
+
+impl fmt::Display for DeepLinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "not a settings link"),
+            Self::BadSignature => write!(f, "the signature does not match"),
+            Self::Expired => write!(f, "the link has expired"),
+        }
+    }
+}
+
+impl std::error::Error for DeepLinkError {}
+
+pub struct DeepLinks {
+    secret: Vec<u8>,
+    ttl: Duration,
+}
+
+impl DeepLinks {
+    pub fn new(secret: &[u8], ttl: Duration) -> Self {
+        Self {
+            secret: secret.to_vec(),
+            ttl,
+        }
+    }
+
+    pub fn from_config(config: &DeepLinkConfig) -> Self {
+        Self::new(config.secret.as_bytes(), config.ttl)
+    }
+
+    /// A payload letting `user_id` configure `group_chat_id`, valid for the
+    /// configured time from `now` (Unix seconds).
+    pub fn sign(&self, group_chat_id: i64, user_id: u64, now: u64) -> String {
+        let expires = now + self.ttl.as_secs();
+        let signature = hex::encode(
+            self.mac(group_chat_id, expires, user_id)
+                .finalize()
+                .into_bytes(),
+        );
+        format!(
+            "{}_{}_{}_{}",
+            PREFIX,
+            group_chat_id,
+            expires,
+            &signature[..SIGNATURE_CHARS]
+        )
+    }
+
+    /// The group a payload was signed for, if `user_id` may use it at `now`.
+    pub fn verify(&self, payload: &str, user_id: u64, now: u64) -> Result<i64, DeepLinkError> {
+        let mut fields = payload.split('_');
+        let (Some(PREFIX), Some(group_chat_id), Some(expires), Some(signature), None) = (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        ) else {
+            return Err(DeepLinkError::Malformed);
+        };
+        let group_chat_id = group_chat_id
+            .parse::<i64>()
+            .map_err(|_| DeepLinkError::Malformed)?;
+        let expires = expires
+            .parse::<u64>()
+            .map_err(|_| DeepLinkError::Malformed)?;
+        let signature = hex::decode(signature).map_err(|_| DeepLinkError::Malformed)?;
+        if signature.len() * 2 != SIGNATURE_CHARS {
+            return Err(DeepLinkError::Malformed);
+        }
+
+        self.mac(group_chat_id, expires, user_id)
+            .verify_truncated_left(&signature)
+            .map_err(|_| DeepLinkError::BadSignature)?;
+        if now > expires {
+            return Err(DeepLinkError::Expired);
+        }
+        Ok(group_chat_id)
+    }
+
+    fn mac(&self, group_chat_id: i64, expires: u64, user_id: u64) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC takes keys of any length");
+        mac.update(format!("{}:{}:{}:{}", PREFIX, group_chat_id, expires, user_id).as_bytes());
+        mac
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GROUP: i64 = -1001234567890;
+    const ADMIN: u64 = 7_000_000_001;
+    const NOW: u64 = 1_760_000_000;
+
+    fn links() -> DeepLinks {
+        DeepLinks::new(b"secret", Duration::from_secs(600))
+    }
+
+    #[test]
+    fn round_trips_within_telegram_limits() {
+        let payload = links().sign(GROUP, ADMIN, NOW);
+        assert!(payload.starts_with("cfg_-1001234567890_1760000600_"));
+        assert!(payload.len() <= 64);
+        assert!(payload
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'));
+        assert_eq!(links().verify(&payload, ADMIN, NOW + 600), Ok(GROUP));
+    }
+
+    #[test]
+    fn rejects_expired_foreign_and_tampered_links() {
+        let payload = links().sign(GROUP, ADMIN, NOW);
+        assert_eq!(
+            links().verify(&payload, ADMIN, NOW + 601),
+            Err(DeepLinkError::Expired)
+        );
+        assert_eq!(
+            links().verify(&payload, ADMIN + 1, NOW),
+            Err(DeepLinkError::BadSignature)
+        );
+        let other_group = payload.replacen("-1001234567890", "-1001234567891", 1);
+        assert_eq!(
+            links().verify(&other_group, ADMIN, NOW),
+            Err(DeepLinkError::BadSignature)
+        );
+        let later = payload.replacen("1760000600", "1760099999", 1);
+        assert_eq!(
+            links().verify(&later, ADMIN, NOW),
+            Err(DeepLinkError::BadSignature)
+        );
+        let other_secret = DeepLinks::new(b"other", Duration::from_secs(600));
+        assert_eq!(
+            other_secret.verify(&payload, ADMIN, NOW),
+            Err(DeepLinkError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_payloads() {
+        for payload in [
+            "available",
+            "",
+            "cfg_-100_1760000600",
+            "cfg_x_1760000600_00",
+            "cfg_-100_1760000600_zz",
+            "cfg_-100_1760000600_00_00",
+        ] {
+            assert_eq!(
+                links().verify(payload, ADMIN, NOW),
+                Err(DeepLinkError::Malformed),
+                "{}",
+                payload
+            );
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+pub type PairResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Where the pollers look up the DEX pairs that buys and sells go through.
+#[async_trait]
+pub trait PairSource: Send + Sync {
+    async fn dex_pairs(&self, token_address: &str) -> PairResult<DexPairs>;
+}
+
+/// The pairs DexScreener lists for a token, on every chain.
+pub struct DexScreenerPairs {
+    client: Client,
+}
+
+impl DexScreenerPairs {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl PairSource for DexScreenerPairs {
+    async fn dex_pairs(&self, token_address: &str) -> PairResult<DexPairs> {
+        crate::get_dex_pairs(self.client.clone(), token_address).await
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct DexPairs {
+    pub pairs: Option<Vec<DexPair>>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DexPair {
+    pub chain_id: String,
+    pub dex_id: String,
+    pub pair_address: String,
+    pub base_token: PairToken,
+    pub quote_token: PairToken,
+    pub price_native: Option<String>,
+    pub price_usd: Option<String>,
+    #[serde(default)]
+    pub liquidity: Option<PairLiquidity>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct PairLiquidity {
+    pub usd: Option<f64>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct PairToken {
+    pub address: String,
+    pub name: String,
+    pub symbol: String,
+}
@@ -0,0 +1,274 @@
+//! The settings dialogue of a private chat: which setting the bot asked for
+//! and waits to receive. It is kept in `Storage`, so an answer sent while the
+//! bot restarts still lands on the setting it was meant for.
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use teloxide::dispatching::dialogue::{self, Dialogue};
+use teloxide::types::ChatId;
+
+use crate::setting_opts::SettingOpts;
+use crate::storage::Storage;
+
+pub type SettingsDialogue = Dialogue<DialogueState, DialogueStorage>;
+
+type StorageFuture<T> =
+    Pin<Box<dyn Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>> + Send>>;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum DialogueState {
+    /// Buttons work, messages that are not commands are ignored.
+    #[default]
+    Idle,
+    Awaiting(Prompt),
+}
+
+/// A setting the bot asked for, with the session it belongs to so the session
+/// can be opened again after a restart.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Prompt {
+    pub group_chat_id: String,
+    /// Empty while the token itself is asked for.
+    pub token_address: String,
+    pub setting: Setting,
+    /// The message showing the prompt, removed once it is answered.
+    #[serde(default)]
+    pub message_id: Option<i32>,
+}
+
+impl Prompt {
+    /// Asks for `setting` of the token in `draft`.
+    pub fn new(draft: &SettingOpts, setting: Setting) -> Self {
+        Self {
+            group_chat_id: draft.group_chat_id.clone(),
+            token_address: draft.token_address.clone(),
+            setting,
+            message_id: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Setting {
+    /// The token to track on the chain picked before.
+    TokenAddress {
+        chain_id: String,
+    },
+    MinBuyAmount,
+    BuyStep,
+    Emoji,
+    TgLink,
+    WebsiteLink,
+    TwitterLink,
+    MinSellAmount,
+    SellEmoji,
+    BuyTemplate,
+    Photo,
+    Video,
+    SellPhoto,
+    SellVideo,
+}
+
+impl Setting {
+    /// The setting a button asks for. The token address is asked for by the
+    /// chain buttons instead.
+    pub fn from_callback(callback_string: &str) -> Option<Self> {
+        let setting = match callback_string {
+            "min_buy_amount" => Self::MinBuyAmount,
+            "buy_step" => Self::BuyStep,
+            "emoji" => Self::Emoji,
+            "tg_link" => Self::TgLink,
+            "website_link" => Self::WebsiteLink,
+            "twitter_link" => Self::TwitterLink,
+            "min_sell_amount" => Self::MinSellAmount,
+            "sell_emoji" => Self::SellEmoji,
+            "buy_template" => Self::BuyTemplate,
+            "photo" => Self::Photo,
+            "video" => Self::Video,
+            "sell_photo" => Self::SellPhoto,
+            "sell_video" => Self::SellVideo,
+            _ => return None,
+        };
+        Some(setting)
+    }
+
+    /// The field name the prompt texts and the buttons use.
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::TokenAddress { .. } => "token_address",
+            Self::MinBuyAmount => "min_buy_amount",
+            Self::BuyStep => "buy_step",
+            Self::Emoji => "emoji",
+            Self::TgLink => "tg_link",
+            Self::WebsiteLink => "website_link",
+            Self::TwitterLink => "twitter_link",
+            Self::MinSellAmount => "min_sell_amount",
+            Self::SellEmoji => "sell_emoji",
+            Self::BuyTemplate => "buy_template",
+            Self::Photo => "photo",
+            Self::Video => "video",
+            Self::SellPhoto => "sell_photo",
+            Self::SellVideo => "sell_video",
+        }
+    }
+
+    pub fn is_media(&self) -> bool {
+        matches!(
+            self,
+            Self::Photo | Self::Video | Self::SellPhoto | Self::SellVideo
+        )
+    }
+}
+
+/// A USD threshold as an admin typed it, e.g. `50` or `12.5`.
+pub fn parse_usd_amount(text: &str) -> Option<f64> {
+    text.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|amount| amount.is_finite() && *amount >= 0.0)
+}
+
+/// A buy step as an admin typed it, whole dollars above zero.
+pub fn parse_buy_step(text: &str) -> Option<i32> {
+    text.trim().parse::<i32>().ok().filter(|step| *step > 0)
+}
+
+/// Keeps dialogues as JSON in the bot's `Storage`.
+pub struct DialogueStorage {
+    storage: Arc<dyn Storage>,
+}
+
+impl DialogueStorage {
+    pub fn new(storage: Arc<dyn Storage>) -> Arc<Self> {
+        Arc::new(Self { storage })
+    }
+}
+
+impl dialogue::Storage<DialogueState> for DialogueStorage {
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn remove_dialogue(self: Arc<Self>, chat_id: ChatId) -> StorageFuture<()> {
+        Box::pin(async move { self.storage.remove_dialogue_state(chat_id.0).await })
+    }
+
+    fn update_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+        dialogue: DialogueState,
+    ) -> StorageFuture<()> {
+        Box::pin(async move {
+            let state = serde_json::to_string(&dialogue)?;
+            self.storage.save_dialogue_state(chat_id.0, state).await
+        })
+    }
+
+    fn get_dialogue(self: Arc<Self>, chat_id: ChatId) -> StorageFuture<Option<DialogueState>> {
+        Box::pin(async move {
+            let Some(state) = self.storage.get_dialogue_state(chat_id.0).await? else {
+                return Ok(None);
+            };
+            match serde_json::from_str(&state) {
+                Ok(dialogue) => Ok(Some(dialogue)),
+                Err(e) => {
+                    // A state written by another version starts over instead
+                    // of locking the chat
+                    log::warn!("Dropping the dialogue of chat {}: {}", chat_id, e);
+                    Ok(None)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use teloxide::dispatching::dialogue::Storage as _;
+
+    const CHAT: ChatId = ChatId(7_000_000_001);
+
+    #[test]
+    fn buttons_and_settings_agree() {
+        for field in crate::i18n::PROMPT_FIELDS {
+            match Setting::from_callback(field) {
+                Some(setting) => assert_eq!(setting.field(), field),
+                None => assert_eq!(field, "token_address"),
+            }
+        }
+        assert_eq!(Setting::from_callback("media_toggle"), None);
+        assert!(Setting::SellVideo.is_media());
+    }
+
+    #[test]
+    fn reads_only_usable_thresholds() {
+        assert_eq!(parse_usd_amount("12.5"), Some(12.5));
+        assert_eq!(parse_usd_amount(" 0 "), Some(0.0));
+        for text in ["-1", "NaN", "inf", "1e400", "$5"] {
+            assert_eq!(parse_usd_amount(text), None, "{}", text);
+        }
+        assert_eq!(parse_buy_step("30"), Some(30));
+        for text in ["0", "-30", "2.5", "99999999999"] {
+            assert_eq!(parse_buy_step(text), None, "{}", text);
+        }
+    }
+
+    #[tokio::test]
+    async fn dialogues_survive_in_storage() {
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+        let dialogues = DialogueStorage::new(storage.clone());
+        let state = DialogueState::Awaiting(Prompt {
+            group_chat_id: "-1001234567890".to_string(),
+            token_address: String::new(),
+            setting: Setting::TokenAddress {
+                chain_id: "ethereum".to_string(),
+            },
+            message_id: Some(42),
+        });
+        dialogues
+            .clone()
+            .update_dialogue(CHAT, state.clone())
+            .await
+            .unwrap();
+
+        // A new adapter over the same storage, as after a restart
+        let restarted = DialogueStorage::new(storage.clone());
+        assert_eq!(
+            restarted.clone().get_dialogue(CHAT).await.unwrap(),
+            Some(state)
+        );
+        restarted.clone().remove_dialogue(CHAT).await.unwrap();
+        assert_eq!(restarted.get_dialogue(CHAT).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn prompts_saved_before_their_message_still_load() {
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+        let saved =
+            r#"{"Awaiting":{"group_chat_id":"-10","token_address":"0xabc","setting":"Emoji"}}"#;
+        storage
+            .save_dialogue_state(CHAT.0, saved.to_string())
+            .await
+            .unwrap();
+        let dialogues = DialogueStorage::new(storage);
+        let Some(DialogueState::Awaiting(prompt)) = dialogues.get_dialogue(CHAT).await.unwrap()
+        else {
+            panic!("the prompt is lost");
+        };
+        assert_eq!(prompt.setting, Setting::Emoji);
+        assert_eq!(prompt.message_id, None);
+    }
+
+    #[tokio::test]
+    async fn unreadable_dialogues_start_over() {
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+        storage
+            .save_dialogue_state(CHAT.0, "\"Bidding\"".to_string())
+            .await
+            .unwrap();
+        let dialogues = DialogueStorage::new(storage);
+        assert_eq!(dialogues.get_dialogue(CHAT).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use log::error;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::chain::Chain;
+use crate::token_transfer::TokenTransfer;
+use crate::tx_info::TxInfo;
+
+pub type ExplorerResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+pub type PageParams = serde_json::Map<String, serde_json::Value>;
+
+/// The block explorer the pollers read transfers and transactions from.
+#[async_trait]
+pub trait BlockExplorer: Send + Sync {
+    /// Returns one page of the token's transfers, newest first. `page_params`
+    /// is the `next_page_params` of the previous page, or `None` for the
+    /// newest page.
+    async fn token_transfers(
+        &self,
+        token_address: &str,
+        page_params: Option<&PageParams>,
+    ) -> ExplorerResult<TokenTransfer>;
+
+    async fn tx_info(&self, tx_hash: &str) -> ExplorerResult<TxInfo>;
+
+    /// Every token transfer of one transaction, e.g. both legs of a swap.
+    async fn tx_token_transfers(&self, tx_hash: &str) -> ExplorerResult<TokenTransfer>;
+
+    /// USD price of the chain's native coin.
+    async fn native_usd_price(&self) -> ExplorerResult<f64>;
+}
+
+#[derive(Deserialize)]
+struct Stats {
+    coin_price: Option<String>,
+}
+
+/// Client for the Blockscout `/api/v2` API.
+pub struct BlockscoutClient {
+    client: Client,
+    api_base: String,
+}
+
+impl BlockscoutClient {
+    /// `api_base` is the API root without a trailing slash, e.g.
+    /// `https://eth.blockscout.com/api/v2`.
+    pub fn new(client: Client, api_base: impl Into<String>) -> Self {
+        Self {
+            client,
+            api_base: api_base.into(),
+        }
+    }
+
+    pub fn for_chain(client: Client, chain: &Chain) -> Self {
+        Self::new(client, chain.explorer_api)
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: Option<&PageParams>,
+    ) -> ExplorerResult<T> {
+        let url = format!("{}{}", self.api_base, path);
+
+        let mut request = self.client.get(&url);
+        if let Some(query) = query {
+            request = request.query(query);
+        }
+        let response = request.send().await?.error_for_status()?;
+
+        let text = response.text().await?;
+
+        match serde_json::from_str::<T>(&text) {
+            Ok(value) => Ok(value),
+            Err(e) => {
+                error!("Deserialization error for {}: {}", url, e);
+                Err(Box::new(e))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl BlockExplorer for BlockscoutClient {
+    async fn token_transfers(
+        &self,
+        token_address: &str,
+        page_params: Option<&PageParams>,
+    ) -> ExplorerResult<TokenTransfer> {
+        self.get(&format!("/tokens/{}/transfers", token_address), page_params)
+            .await
+    }
+
+    async fn tx_info(&self, tx_hash: &str) -> ExplorerResult<TxInfo> {
+        self.get(&format!("/transactions/{}", tx_hash), None).await
+    }
+
+    async fn tx_token_transfers(&self, tx_hash: &str) -> ExplorerResult<TokenTransfer> {
+        self.get(&format!("/transactions/{}/token-transfers", tx_hash), None)
+            .await
+    }
+
+    async fn native_usd_price(&self) -> ExplorerResult<f64> {
+        let stats: Stats = self.get("/stats", None).await?;
+        let coin_price = stats.coin_price.ok_or("the explorer has no coin price")?;
+        Ok(coin_price.parse()?)
+    }
+}
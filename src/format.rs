@@ -0,0 +1,369 @@
+//! Number formatting for alerts.
+//!
+//! Three styles are used:
+//! - [`price`]: four significant digits, with the subscript-zero notation for
+//!   tiny prices, e.g. `0.0₆1234` for `0.0000001234`.
+//! - [`amount`]: token amounts, written out up to a million and compact above.
+//! - [`compact`]: USD values with a K/M/B/T suffix, e.g. `1.25M`.
+//!
+//! Whole digits are never rounded away below the compact range, and the
+//! thousands and decimal separators follow the [`NumberLocale`].
+
+/// Significant digits of a price or of the fraction of an amount.
+const SIGNIFICANT_DIGITS: usize = 4;
+
+/// Significant digits of a compact value.
+const COMPACT_DIGITS: usize = 3;
+
+/// Amounts from here on are written compact.
+const COMPACT_AMOUNT_FROM: f64 = 1_000_000.0;
+
+/// Leading fraction zeros from which the subscript notation is used.
+const SUBSCRIPT_MIN_ZEROS: usize = 4;
+
+const SUFFIXES: [&str; 5] = ["", "K", "M", "B", "T"];
+
+/// Separator conventions, named after a language using them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NumberLocale {
+    /// `1,234.5`
+    #[default]
+    En,
+    /// `1.234,5`
+    De,
+    /// `1 234,5`, with a narrow no-break space.
+    Fr,
+}
+
+impl NumberLocale {
+    /// The convention for a language tag such as `de` or `pt-BR`, English if
+    /// the language is unknown.
+    pub fn from_tag(tag: &str) -> Self {
+        let language = tag.split(['-', '_']).next().unwrap_or("");
+        match language.to_lowercase().as_str() {
+            "de" | "es" | "it" | "pt" | "nl" | "id" | "tr" | "vi" => Self::De,
+            "fr" | "ru" | "uk" | "pl" | "cs" | "sv" | "fi" | "nb" => Self::Fr,
+            _ => Self::En,
+        }
+    }
+
+    pub fn thousands_separator(self) -> char {
+        match self {
+            Self::En => ',',
+            Self::De => '.',
+            Self::Fr => '\u{202F}',
+        }
+    }
+
+    pub fn decimal_separator(self) -> char {
+        match self {
+            Self::En => '.',
+            Self::De | Self::Fr => ',',
+        }
+    }
+}
+
+/// A token or native coin price, e.g. `0.002`, `1,234` or `0.0₆1234`.
+pub fn price(value: f64, locale: NumberLocale) -> String {
+    format_with(value, locale, |abs| {
+        Decimal::round(abs, SIGNIFICANT_DIGITS).keep_whole_digits(abs)
+    })
+}
+
+/// A token amount, e.g. `20,000`, `0.003611` or `1.5M`.
+pub fn amount(value: f64, locale: NumberLocale) -> String {
+    if value.abs() >= COMPACT_AMOUNT_FROM {
+        compact(value, locale)
+    } else {
+        price(value, locale)
+    }
+}
+
+/// A value with a K/M/B/T suffix from a thousand on, e.g. `40`, `3K` or
+/// `1.25M`.
+pub fn compact(value: f64, locale: NumberLocale) -> String {
+    format_with(value, locale, |abs| {
+        let rounded = Decimal::round(abs, COMPACT_DIGITS);
+        if rounded.exponent < 3 {
+            return Decimal::round(abs, SIGNIFICANT_DIGITS).keep_whole_digits(abs);
+        }
+        let tier = (rounded.exponent / 3).min(SUFFIXES.len() as i32 - 1);
+        // Past the last suffix the whole digits are kept, e.g. `1,250T`
+        let whole_digits = (rounded.exponent - 3 * tier + 1) as usize;
+        let mut scaled = if whole_digits > COMPACT_DIGITS {
+            Decimal::round(abs, whole_digits)
+        } else {
+            rounded
+        };
+        scaled.exponent -= 3 * tier;
+        scaled.suffix = SUFFIXES[tier as usize];
+        scaled
+    })
+}
+
+fn format_with(value: f64, locale: NumberLocale, decimal: impl Fn(f64) -> Decimal) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    let decimal = decimal(value.abs());
+    let sign = if value < 0.0 && !decimal.is_zero() {
+        "-"
+    } else {
+        ""
+    };
+    format!("{}{}", sign, decimal.render(locale))
+}
+
+/// A non-negative number as significant digits times a power of ten:
+/// `digits = "1234", exponent = -7` is `1.234e-7`.
+struct Decimal {
+    digits: String,
+    exponent: i32,
+    suffix: &'static str,
+}
+
+impl Decimal {
+    /// Rounds to `significant` digits, dropping trailing zeros.
+    fn round(abs: f64, significant: usize) -> Self {
+        let scientific = format!("{:.*e}", significant.max(1) - 1, abs);
+        let (mantissa, exponent) = scientific
+            .split_once('e')
+            .expect("`{:e}` always has an exponent");
+        let digits = mantissa.replace('.', "");
+        let digits = match digits.trim_end_matches('0') {
+            "" => "0".to_string(),
+            trimmed => trimmed.to_string(),
+        };
+        Self {
+            digits,
+            exponent: exponent.parse().expect("`{:e}` exponents are integers"),
+            suffix: "",
+        }
+    }
+
+    /// Rounds again so no whole digit is lost, e.g. `123456` stays `123456`
+    /// instead of `123500`.
+    fn keep_whole_digits(self, abs: f64) -> Self {
+        let whole_digits = self.exponent + 1;
+        if whole_digits > SIGNIFICANT_DIGITS as i32 {
+            Self::round(abs, whole_digits as usize)
+        } else {
+            self
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.digits == "0"
+    }
+
+    fn render(&self, locale: NumberLocale) -> String {
+        if self.is_zero() {
+            return format!("0{}", self.suffix);
+        }
+        let (whole, fraction) = if self.exponent >= 0 {
+            let whole_len = self.exponent as usize + 1;
+            if self.digits.len() > whole_len {
+                let (whole, fraction) = self.digits.split_at(whole_len);
+                (whole.to_string(), fraction.to_string())
+            } else {
+                (format!("{:0<whole_len$}", self.digits), String::new())
+            }
+        } else {
+            let zeros = (-self.exponent - 1) as usize;
+            if zeros >= SUBSCRIPT_MIN_ZEROS {
+                return format!(
+                    "0{}0{}{}{}",
+                    locale.decimal_separator(),
+                    subscript(zeros),
+                    self.digits,
+                    self.suffix
+                );
+            }
+            (
+                "0".to_string(),
+                format!("{}{}", "0".repeat(zeros), self.digits),
+            )
+        };
+
+        let mut rendered = group_thousands(&whole, locale.thousands_separator());
+        if !fraction.is_empty() {
+            rendered.push(locale.decimal_separator());
+            rendered.push_str(&fraction);
+        }
+        rendered.push_str(self.suffix);
+        rendered
+    }
+}
+
+fn group_thousands(whole: &str, separator: char) -> String {
+    let mut grouped = String::with_capacity(whole.len() + whole.len() / 3 * 3);
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+fn subscript(n: usize) -> String {
+    n.to_string()
+        .chars()
+        .map(|digit| match digit {
+            '0' => '₀',
+            '1' => '₁',
+            '2' => '₂',
+            '3' => '₃',
+            '4' => '₄',
+            '5' => '₅',
+            '6' => '₆',
+            '7' => '₇',
+            '8' => '₈',
+            _ => '₉',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EN: NumberLocale = NumberLocale::En;
+
+    /// Reads an `En` rendering back, expanding the subscript and suffix.
+    fn parse_en(rendered: &str) -> f64 {
+        let mut plain = String::new();
+        let mut zeros = String::new();
+        for c in rendered.chars() {
+            match c {
+                '₀'..='₉' => zeros.push(char::from_digit(c as u32 - '₀' as u32, 10).unwrap()),
+                ',' => {}
+                _ => {
+                    if !zeros.is_empty() {
+                        // `0.0₆1234`: the written zero is one of the six
+                        plain.push_str(&"0".repeat(zeros.parse::<usize>().unwrap() - 1));
+                        zeros.clear();
+                    }
+                    plain.push(c);
+                }
+            }
+        }
+        let (number, multiplier) = match plain.chars().last() {
+            Some('K') => (&plain[..plain.len() - 1], 1e3),
+            Some('M') => (&plain[..plain.len() - 1], 1e6),
+            Some('B') => (&plain[..plain.len() - 1], 1e9),
+            Some('T') => (&plain[..plain.len() - 1], 1e12),
+            _ => (plain.as_str(), 1.0),
+        };
+        number.parse::<f64>().unwrap() * multiplier
+    }
+
+    fn close(rendered: f64, value: f64, significant: usize) -> bool {
+        (rendered - value).abs() <= value.abs() * 10_f64.powi(1 - significant as i32) / 2.0 * 1.001
+    }
+
+    #[test]
+    fn tiny_prices_use_subscript_zeros() {
+        assert_eq!(price(0.0000001234, EN), "0.0₆1234");
+        assert_eq!(price(0.00001234, EN), "0.0₄1234");
+        assert_eq!(price(0.0001234, EN), "0.0001234");
+        assert_eq!(price(0.000000000000123456, EN), "0.0₁₂1235");
+        assert_eq!(price(0.002, EN), "0.002");
+        assert_eq!(price(0.0, EN), "0");
+    }
+
+    #[test]
+    fn keeps_whole_digits_and_groups_thousands() {
+        assert_eq!(amount(20_000.0, EN), "20,000");
+        assert_eq!(amount(123_456.7, EN), "123,457");
+        assert_eq!(amount(1_500_000.0, EN), "1.5M");
+        assert_eq!(amount(0.0036105297, EN), "0.003611");
+        assert_eq!(price(1234.5678, EN), "1,235");
+        assert_eq!(price(12.345678, EN), "12.35");
+    }
+
+    #[test]
+    fn compact_suffixes() {
+        assert_eq!(compact(40.0, EN), "40");
+        assert_eq!(compact(0.35, EN), "0.35");
+        assert_eq!(compact(999.4, EN), "999.4");
+        assert_eq!(compact(999.96, EN), "1K");
+        assert_eq!(compact(3_000.0, EN), "3K");
+        assert_eq!(compact(1_254_000.0, EN), "1.25M");
+        assert_eq!(compact(999_600_000.0, EN), "1B");
+        assert_eq!(compact(7.1e12, EN), "7.1T");
+        assert_eq!(compact(1.25e15, EN), "1,250T");
+        assert_eq!(compact(-2_500.0, EN), "-2.5K");
+    }
+
+    #[test]
+    fn separators_follow_the_locale() {
+        let de = NumberLocale::from_tag("de-DE");
+        assert_eq!(de, NumberLocale::De);
+        assert_eq!(amount(12_345.0, de), "12.345");
+        assert_eq!(amount(123.45, de), "123,5");
+        assert_eq!(compact(1_250_000.0, de), "1,25M");
+        assert_eq!(price(0.0000001234, de), "0,0₆1234");
+        assert_eq!(
+            amount(12_345.0, NumberLocale::from_tag("fr")),
+            "12\u{202F}345"
+        );
+        assert_eq!(NumberLocale::from_tag("xx"), NumberLocale::En);
+    }
+
+    proptest! {
+        #[test]
+        fn prices_keep_four_significant_digits(value in -1e15_f64..1e15) {
+            let rendered = price(value, EN);
+            prop_assert!(close(parse_en(&rendered), value, SIGNIFICANT_DIGITS), "{} -> {}", value, rendered);
+        }
+
+        #[test]
+        fn tiny_prices_round_trip(mantissa in 1.0_f64..9.99, exponent in -30_i32..-1) {
+            let value = mantissa * 10_f64.powi(exponent);
+            let rendered = price(value, EN);
+            prop_assert!(close(parse_en(&rendered), value, SIGNIFICANT_DIGITS), "{} -> {}", value, rendered);
+            prop_assert_eq!(rendered.chars().any(|c| ('₀'..='₉').contains(&c)), exponent <= -5);
+        }
+
+        #[test]
+        fn compact_values_keep_three_significant_digits(value in 0.0_f64..1e18) {
+            let rendered = compact(value, EN);
+            prop_assert!(close(parse_en(&rendered), value, COMPACT_DIGITS), "{} -> {}", value, rendered);
+        }
+
+        #[test]
+        fn whole_digits_are_grouped_by_three(value in 0_u64..1_000_000) {
+            let rendered = amount(value as f64, EN);
+            let groups: Vec<&str> = rendered.split(',').collect();
+            prop_assert!(groups[0].len() <= 3 && !groups[0].is_empty());
+            prop_assert!(groups[1..].iter().all(|group| group.len() == 3));
+            prop_assert_eq!(rendered.replace(',', ""), value.to_string());
+        }
+
+        #[test]
+        fn locales_only_change_separators(value in -1e9_f64..1e9) {
+            let en = amount(value, EN);
+            let de = amount(value, NumberLocale::De);
+            let swapped: String = en
+                .chars()
+                .map(|c| match c {
+                    ',' => '.',
+                    '.' => ',',
+                    c => c,
+                })
+                .collect();
+            prop_assert_eq!(de, swapped);
+        }
+
+        #[test]
+        fn never_panics(value in any::<f64>()) {
+            for locale in [NumberLocale::En, NumberLocale::De, NumberLocale::Fr] {
+                prop_assert!(!price(value, locale).is_empty());
+                prop_assert!(!amount(value, locale).is_empty());
+                prop_assert!(!compact(value, locale).is_empty());
+            }
+        }
+    }
+}
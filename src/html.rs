@@ -0,0 +1,233 @@
+//! Helpers for messages sent with Telegram's HTML parse mode, where a stray
+//! `<` or `&` makes Telegram reject the whole message.
+
+use reqwest::Url;
+use std::fmt;
+
+use crate::i18n::Language;
+
+/// Tags Telegram accepts.
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "b",
+    "strong",
+    "i",
+    "em",
+    "u",
+    "ins",
+    "s",
+    "strike",
+    "del",
+    "code",
+    "pre",
+    "span",
+    "tg-spoiler",
+    "tg-emoji",
+    "blockquote",
+];
+
+const LINK_SCHEMES: &[&str] = &["http", "https", "tg"];
+
+/// Escapes text for use between tags or in an attribute value.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The escaped `href` value for `url`, or `None` if it is empty or not an
+/// absolute http(s) or tg link.
+pub fn safe_href(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.is_empty() || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return None;
+    }
+    let parsed = Url::parse(url).ok()?;
+    if !LINK_SCHEMES.contains(&parsed.scheme()) {
+        return None;
+    }
+    if parsed.scheme() != "tg" && parsed.host_str().is_none_or(str::is_empty) {
+        return None;
+    }
+    Some(escape(url))
+}
+
+/// Markup Telegram would reject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkupError {
+    Ampersand,
+    GreaterThan,
+    LessThan,
+    UnclosedTag(String),
+    MismatchedTag { closing: String, open: String },
+    UnopenedTag(String),
+    UnsupportedTag(String),
+    LinkSyntax,
+    InvalidLink(String),
+}
+
+impl MarkupError {
+    pub fn localized(&self, language: Language) -> String {
+        match self {
+            Self::Ampersand => language.text("markup-error-ampersand"),
+            Self::GreaterThan => language.text("markup-error-greater-than"),
+            Self::LessThan => language.text("markup-error-less-than"),
+            Self::UnclosedTag(tag) => {
+                language.text_with("markup-error-unclosed-tag", &[("tag", tag)])
+            }
+            Self::MismatchedTag { closing, open } => language.text_with(
+                "markup-error-mismatched-tag",
+                &[("closing", closing), ("open", open)],
+            ),
+            Self::UnopenedTag(tag) => {
+                language.text_with("markup-error-unopened-tag", &[("tag", tag)])
+            }
+            Self::UnsupportedTag(tag) => {
+                language.text_with("markup-error-unsupported-tag", &[("tag", tag)])
+            }
+            Self::LinkSyntax => language.text("markup-error-link-syntax"),
+            Self::InvalidLink(href) => {
+                language.text_with("markup-error-invalid-link", &[("href", href)])
+            }
+        }
+    }
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localized(Language::En))
+    }
+}
+
+impl std::error::Error for MarkupError {}
+
+/// Checks markup written by an admin: only tags Telegram knows, closed in
+/// order, links with a valid `href`, and `&` only as an entity.
+pub fn check_markup(html: &str) -> Result<(), MarkupError> {
+    let mut open_tags: Vec<String> = Vec::new();
+    let mut rest = html;
+    while let Some(i) = rest.find(['<', '>', '&']) {
+        let (special, after) = rest[i..].split_at(1);
+        match special {
+            "&" => {
+                let entity = after.split(';').next().unwrap_or_default();
+                if !after.contains(';') || !is_entity(entity) {
+                    return Err(MarkupError::Ampersand);
+                }
+                rest = &after[entity.len() + 1..];
+            }
+            ">" => return Err(MarkupError::GreaterThan),
+            _ => {
+                let Some(end) = after.find('>') else {
+                    return Err(MarkupError::LessThan);
+                };
+                check_tag(&after[..end], &mut open_tags)?;
+                rest = &after[end + 1..];
+            }
+        }
+    }
+    match open_tags.pop() {
+        Some(tag) => Err(MarkupError::UnclosedTag(tag)),
+        None => Ok(()),
+    }
+}
+
+fn check_tag(tag: &str, open_tags: &mut Vec<String>) -> Result<(), MarkupError> {
+    if let Some(name) = tag.strip_prefix('/') {
+        let name = name.trim().to_lowercase();
+        return match open_tags.pop() {
+            Some(open) if open == name => Ok(()),
+            Some(open) => Err(MarkupError::MismatchedTag {
+                closing: name,
+                open,
+            }),
+            None => Err(MarkupError::UnopenedTag(name)),
+        };
+    }
+
+    let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+    let name = name.to_lowercase();
+    if !ALLOWED_TAGS.contains(&name.as_str()) {
+        return Err(MarkupError::UnsupportedTag(name));
+    }
+    if name == "a" {
+        let href = attributes
+            .trim()
+            .strip_prefix("href=\"")
+            .and_then(|href| href.strip_suffix('"'))
+            .ok_or(MarkupError::LinkSyntax)?;
+        if safe_href(&unescape_quotes(href)).is_none() {
+            return Err(MarkupError::InvalidLink(href.to_string()));
+        }
+    }
+    open_tags.push(name);
+    Ok(())
+}
+
+fn is_entity(entity: &str) -> bool {
+    match entity.strip_prefix('#') {
+        Some(hex) if hex.starts_with(['x', 'X']) => {
+            hex.len() > 1 && hex[1..].chars().all(|c| c.is_ascii_hexdigit())
+        }
+        Some(decimal) => !decimal.is_empty() && decimal.chars().all(|c| c.is_ascii_digit()),
+        None => !entity.is_empty() && entity.chars().all(|c| c.is_ascii_alphabetic()),
+    }
+}
+
+fn unescape_quotes(value: &str) -> String {
+    value.replace("&quot;", "\"").replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_markup_characters() {
+        assert_eq!(
+            escape("<b>Tom & \"Jerry\"</b>"),
+            "&lt;b&gt;Tom &amp; &quot;Jerry&quot;&lt;/b&gt;"
+        );
+        assert_eq!(escape("GORILLA 🦍"), "GORILLA 🦍");
+    }
+
+    #[test]
+    fn accepts_only_absolute_web_and_tg_links() {
+        assert_eq!(
+            safe_href("https://x.com/a?b=1&c=\"2\""),
+            Some("https://x.com/a?b=1&amp;c=&quot;2&quot;".to_string())
+        );
+        assert!(safe_href("tg://resolve?domain=gorilla").is_some());
+        assert_eq!(safe_href(""), None);
+        assert_eq!(safe_href("t.me/gorilla"), None);
+        assert_eq!(safe_href("javascript:alert(1)"), None);
+        assert_eq!(safe_href("https://x.com/a b"), None);
+        assert_eq!(safe_href("https://"), None);
+    }
+
+    #[test]
+    fn checks_admin_markup() {
+        assert!(check_markup("<b>Buy</b> &amp; <a href=\"https://t.me/x\">hold</a>").is_ok());
+        assert!(check_markup("<B>Buy</B> &#128013; &#x1F98D;").is_ok());
+        assert!(check_markup("Buy & hold").is_err());
+        assert!(check_markup("1 < 2").is_err());
+        assert!(check_markup("2 > 1").is_err());
+        assert!(check_markup("<b>Buy").is_err());
+        assert!(check_markup("<b><i>Buy</b></i>").is_err());
+        assert!(check_markup("<script>x</script>").is_err());
+        assert!(check_markup("<a href=\"\">x</a>").is_err());
+        assert_eq!(check_markup("<a>x</a>"), Err(MarkupError::LinkSyntax));
+        assert_eq!(
+            check_markup("<b>Buy").unwrap_err().localized(Language::De),
+            "<b> wird nie geschlossen"
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Bot texts in every supported language, from the Fluent catalogs in
+//! `locales/`. A group's language applies to its settings keyboard and its
+//! alerts.
+
+use std::sync::OnceLock;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use log::warn;
+use unic_langid::LanguageIdentifier;
+
+use crate::format::NumberLocale;
+use crate::setting_opts::SettingOpts;
+
+/// Settings the bot asks for with a prompt, by callback data.
+pub const PROMPT_FIELDS: [&str; 14] = [
+    "token_address",
+    "min_buy_amount",
+    "buy_step",
+    "emoji",
+    "tg_link",
+    "website_link",
+    "twitter_link",
+    "min_sell_amount",
+    "sell_emoji",
+    "buy_template",
+    "photo",
+    "video",
+    "sell_photo",
+    "sell_video",
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    En,
+    De,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Self::En, Self::De];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::De => "de",
+        }
+    }
+
+    /// The language for a code such as `de` or Telegram's `de-AT`, or `None`
+    /// if it is not supported.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let primary = code.split(['-', '_']).next().unwrap_or("");
+        Self::ALL
+            .into_iter()
+            .find(|language| language.code().eq_ignore_ascii_case(primary))
+    }
+
+    /// The language a group picked, English if it never did.
+    pub fn of(setting_opts: &SettingOpts) -> Self {
+        Self::from_code(&setting_opts.language).unwrap_or_default()
+    }
+
+    /// The language's own name, e.g. `Deutsch`.
+    pub fn name(self) -> String {
+        self.text("language-name")
+    }
+
+    pub fn number_locale(self) -> NumberLocale {
+        NumberLocale::from_tag(self.code())
+    }
+
+    /// The prompt asking for a setting in `PROMPT_FIELDS`.
+    pub fn prompt(self, field: &str) -> String {
+        self.text(&prompt_id(field))
+    }
+
+    /// The text with the id, falling back to English and then to the id.
+    pub fn text(self, id: &str) -> String {
+        self.text_with(id, &[])
+    }
+
+    /// The text with the id, its `{ $name }` variables filled from `args`.
+    pub fn text_with(self, id: &str, args: &[(&str, &str)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, *value);
+        }
+        for language in [self, Self::En] {
+            let bundle = bundle(language);
+            let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+                continue;
+            };
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+            if !errors.is_empty() {
+                warn!(
+                    "Could not format text {} in {}: {:?}",
+                    id,
+                    language.code(),
+                    errors
+                );
+            }
+            return text.into_owned();
+        }
+        warn!("Missing text {}", id);
+        id.to_string()
+    }
+}
+
+fn prompt_id(field: &str) -> String {
+    format!("prompt-{}", field.replace('_', "-"))
+}
+
+fn catalog(language: Language) -> &'static str {
+    match language {
+        Language::En => include_str!("../locales/en.ftl"),
+        Language::De => include_str!("../locales/de.ftl"),
+    }
+}
+
+fn bundle(language: Language) -> &'static FluentBundle<FluentResource> {
+    static BUNDLES: OnceLock<Vec<FluentBundle<FluentResource>>> = OnceLock::new();
+    let bundles = BUNDLES.get_or_init(|| {
+        Language::ALL
+            .into_iter()
+            .map(|language| {
+                let id: LanguageIdentifier =
+                    language.code().parse().expect("language codes are valid");
+                let mut bundle = FluentBundle::new_concurrent(vec![id]);
+                // Telegram shows the Unicode isolation marks around variables
+                bundle.set_use_isolating(false);
+                let resource = FluentResource::try_new(catalog(language).to_string())
+                    .expect("the catalogs are valid Fluent");
+                bundle
+                    .add_resource(resource)
+                    .expect("the catalogs have no duplicate ids");
+                bundle
+            })
+            .collect()
+    });
+    let index = Language::ALL
+        .iter()
+        .position(|other| *other == language)
+        .expect("every language has a bundle");
+    &bundles[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(language: Language) -> Vec<&'static str> {
+        catalog(language)
+            .lines()
+            .filter_map(|line| line.split_once(" ="))
+            .map(|(id, _)| id)
+            .filter(|id| !id.is_empty() && !id.starts_with([' ', '#']))
+            .collect()
+    }
+
+    #[test]
+    fn catalogs_have_the_same_texts() {
+        let english = ids(Language::En);
+        assert!(english.contains(&"choose-chain"));
+        for language in Language::ALL {
+            assert_eq!(ids(language), english, "{}", language.code());
+        }
+    }
+
+    #[test]
+    fn looks_up_texts_by_language() {
+        assert_eq!(Language::En.name(), "English");
+        assert_eq!(Language::De.name(), "Deutsch");
+        assert_eq!(
+            Language::De.text_with("token-not-found", &[("token", "0xabc")]),
+            "Der Token 0xabc wurde nicht gefunden."
+        );
+        assert_eq!(
+            Language::En.text("template-error-unclosed-placeholder"),
+            "a { is never closed, write {{ for a brace"
+        );
+        assert_eq!(Language::De.text("no-such-text"), "no-such-text");
+        // Texts can refer to each other
+        assert!(Language::De.prompt("buy_template").contains("\"standard\""));
+    }
+
+    #[test]
+    fn reads_stored_and_telegram_codes() {
+        assert_eq!(Language::from_code("de"), Some(Language::De));
+        assert_eq!(Language::from_code("de-AT"), Some(Language::De));
+        assert_eq!(Language::from_code("EN"), Some(Language::En));
+        assert_eq!(Language::from_code("xx"), None);
+        assert_eq!(Language::De.number_locale(), NumberLocale::De);
+    }
+}
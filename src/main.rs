@@ -1,33 +1,53 @@
 use log::error;
-use mysql::prelude::*;
-use mysql::*;
 use reqwest::Client;
 use std::sync::Arc;
 use teloxide::types::{
-    ChatId, ForceReply, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, ReplyMarkup,
+    BotCommand, ChatId, InlineKeyboardButton, InlineKeyboardMarkup, MessageId, User,
 };
-use teloxide::{prelude::*, utils::command::BotCommands};
-use tokio::sync::RwLock;
-
+use teloxide::{prelude::*, utils::command::BotCommands, ApiError, RequestError};
+
+pub mod amount;
+pub mod chain;
+pub mod config;
+pub mod deep_link;
+pub mod dex_pairs;
+pub mod dialogue;
+pub mod explorer;
+pub mod format;
+pub mod html;
+pub mod i18n;
+pub mod migrations;
+pub mod mysql_storage;
+pub mod price;
 pub mod regex;
+pub mod session;
 pub mod setting_opts;
+pub mod storage;
+pub mod swap;
+pub mod template;
 pub mod token_overview;
 pub mod token_transfer;
+pub mod transfer_kind;
 pub mod tx_info;
 pub mod user_info;
-
+pub mod watcher;
+
+use chain::*;
+use config::*;
+use deep_link::*;
+use dex_pairs::*;
+use dialogue::*;
+use explorer::BlockscoutClient;
+use i18n::*;
+use mysql_storage::*;
+use price::*;
 use regex::*;
+use session::*;
 use setting_opts::*;
-use token_overview::*;
-use token_transfer::*;
-use tx_info::*;
+use storage::*;
+use template::*;
 use user_info::*;
-
-// Add this function to establish database connection
-fn get_conn_pool() -> Pool {
-    let url = "mysql://root:@localhost:3306/gorilla_buy_bot";
-    Pool::new(url).unwrap()
-}
+use watcher::*;
 
 #[derive(BotCommands, Clone)]
 #[command(
@@ -35,10 +55,18 @@ fn get_conn_pool() -> Pool {
     description = "These commands are supported:"
 )]
 enum Command {
-    #[command(description = "Setup the language", parse_with = "split")]
+    #[command(
+        description = "Configure the buy bot and its language",
+        parse_with = "split"
+    )]
     Settings { bot_username: String },
     #[command(description = "Show the start message", parse_with = "split")]
-    Start { availability: String },
+    Start { payload: String },
+    #[command(
+        description = "List and manage the tracked tokens",
+        parse_with = "split"
+    )]
+    Tokens { bot_username: String },
 }
 
 #[tokio::main]
@@ -47,44 +75,148 @@ async fn main() {
     pretty_env_logger::init();
     log::info!("Starting Gorilla Buy...");
 
+    // Initialize database connection
+    let db_config = match DbConfig::from_env() {
+        Ok(db_config) => db_config,
+        Err(e) => {
+            error!("Invalid database configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mysql_storage = match MysqlStorage::connect(&db_config) {
+        Ok(storage) => storage,
+        Err(e) => {
+            error!("Could not connect to the database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // `--migrations-status` and `--migrations-dry-run` only read the schema, then exit
+    if std::env::args().any(|arg| arg == "--migrations-status") {
+        match mysql_storage.migration_status() {
+            Ok(status) => print!("{}", status),
+            Err(e) => {
+                error!("Could not read the schema version: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if std::env::args().any(|arg| arg == "--migrations-dry-run") {
+        match mysql_storage.migration_status() {
+            Ok(status) if status.pending.is_empty() => println!("Schema is up to date."),
+            Ok(status) => {
+                println!("{} migration(s) would be applied:\n", status.pending.len());
+                print!("{}", status.pending_sql());
+            }
+            Err(e) => {
+                error!("Could not read the schema version: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Bring the schema up to date before anything touches it
+    let storage: Arc<dyn Storage> = Arc::new(mysql_storage);
+    if let Err(e) = storage.init().await {
+        error!("Failed to migrate database: {}", e);
+        std::process::exit(1);
+    }
+
     let bot: Bot = Bot::from_env();
     // let bot_arc = Arc::new(bot.clone());
 
-    let bot_commands = Command::bot_commands();
-    if bot.set_my_commands(bot_commands).await.is_err() {
-        log::warn!("Could not set up the commands.");
+    for language in Language::ALL {
+        let bot_commands = Command::bot_commands()
+            .into_iter()
+            .map(|command| {
+                let id = format!("command-{}", command.command.trim_start_matches('/'));
+                BotCommand::new(command.command, language.text(&id))
+            })
+            .collect::<Vec<_>>();
+        let request = bot.set_my_commands(bot_commands);
+        let request = match language {
+            Language::En => request,
+            _ => request.language_code(language.code()),
+        };
+        if request.await.is_err() {
+            log::warn!("Could not set up the commands in {}.", language.code());
+        }
     }
 
-    let setting_opts_arc = Arc::new(RwLock::new(SettingOpts::default()));
-    // println!("initial setting_opts_arc: {:?}", setting_opts_arc.read().await);
+    let sessions = Arc::new(SessionStore::new());
 
-    // Initialize database connection
-    let pool = get_conn_pool();
+    let deep_links = match DeepLinkConfig::from_env() {
+        Ok(deep_link_config) => Arc::new(DeepLinks::from_config(&deep_link_config)),
+        Err(e) => {
+            error!("Invalid settings link configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let price_config = match PriceConfig::from_env() {
+        Ok(price_config) => price_config,
+        Err(e) => {
+            error!("Invalid price provider configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let prices = match price_providers(&price_config) {
+        Ok(prices) => Arc::new(prices),
+        Err(e) => {
+            error!("Invalid price provider configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let request_client = Client::new();
+    let explorer_client = request_client.clone();
+    let watchers = Arc::new(WatcherRegistry::new(
+        bot.clone(),
+        prices,
+        price_config.swap_pricing,
+        Box::new(move |chain| {
+            Arc::new(BlockscoutClient::for_chain(explorer_client.clone(), chain))
+        }),
+        Arc::new(DexScreenerPairs::new(request_client)),
+    ));
 
-    // Create tables if they don't exist
-    init_database(&pool).expect("Failed to initialize database");
+    // Pick up every token that was being tracked before the restart
+    resume_buy_watchers(&watchers, storage.as_ref()).await;
 
-    let callback_handler = Update::filter_callback_query().endpoint(answer_button);
+    // Prompts that were waiting for an answer before the restart still are
+    let dialogue_storage = DialogueStorage::new(storage.clone());
+
+    let callback_handler = Update::filter_callback_query()
+        .enter_dialogue::<CallbackQuery, DialogueStorage, DialogueState>()
+        .endpoint(answer_button);
 
     let message_handler = Update::filter_message()
         .branch(
-            dptree::filter(|msg: Message| {
-                // Check if this message is a reply to a bot's message with ForceReply
-                msg.reply_to_message()
-                    .and_then(|reply| reply.from())
-                    .map_or(false, |user| user.is_bot)
-            })
-            .branch(dptree::endpoint(answer_replyed_message)),
+            dptree::entry()
+                .filter_command::<Command>()
+                .endpoint(answer_command),
         )
-        .filter_command::<Command>()
-        .endpoint(answer_command);
+        .branch(
+            // Only the private settings chat is ever asked for a setting
+            dptree::filter(|msg: Message| msg.chat.is_private())
+                .enter_dialogue::<Message, DialogueStorage, DialogueState>()
+                .branch(dptree::case![DialogueState::Awaiting(prompt)].endpoint(answer_dialogue)),
+        );
 
     let handler = dptree::entry()
         .branch(message_handler)
         .branch(callback_handler);
 
     Dispatcher::builder(bot, handler)
-        .dependencies(dptree::deps![setting_opts_arc.clone()])
+        .dependencies(dptree::deps![
+            sessions.clone(),
+            deep_links.clone(),
+            dialogue_storage.clone(),
+            watchers.clone(),
+            storage.clone()
+        ])
         .enable_ctrlc_handler()
         .build()
         .dispatch()
@@ -95,7 +227,10 @@ async fn answer_command(
     bot: Bot,
     msg: Message,
     cmd: Command,
-    setting_opts_arc: Arc<RwLock<SettingOpts>>,
+    sessions: Arc<SessionStore>,
+    storage: Arc<dyn Storage>,
+    deep_links: Arc<DeepLinks>,
+    dialogue_storage: Arc<DialogueStorage>,
 ) -> ResponseResult<()> {
     let chat_type = match msg.chat.kind {
         teloxide::types::ChatKind::Private { .. } => "a private chat".to_string(),
@@ -103,17 +238,36 @@ async fn answer_command(
             teloxide::types::PublicChatKind::Group { .. } => "a group".to_string(),
             teloxide::types::PublicChatKind::Supergroup { .. } => "a supergroup".to_string(),
             teloxide::types::PublicChatKind::Channel { .. } => "a channel".to_string(),
-        }, // _ => {
-           //     bot.send_message(msg.chat.id, "Could not determine chat type.")
-           //         .await?;
-           //     return Ok(());
-           // }
+        },
     };
     let _ = match cmd {
         Command::Settings { bot_username } => {
-            settings_command(bot, msg, bot_username, chat_type, setting_opts_arc).await
+            settings_command(bot, msg, bot_username, chat_type, storage, deep_links).await
+        }
+        Command::Start { payload } => {
+            start_command(
+                bot,
+                msg,
+                payload,
+                sessions,
+                storage,
+                deep_links,
+                dialogue_storage,
+            )
+            .await
+        }
+        Command::Tokens { bot_username } => {
+            tokens_command(
+                bot,
+                msg,
+                bot_username,
+                chat_type,
+                sessions,
+                storage,
+                deep_links,
+            )
+            .await
         }
-        Command::Start { availability } => start_command(bot, msg, availability).await,
     };
     Ok(())
 }
@@ -123,72 +277,232 @@ async fn settings_command(
     msg: Message,
     bot_username: String,
     chat_type: String,
-    setting_opts_arc: Arc<RwLock<SettingOpts>>,
+    storage: Arc<dyn Storage>,
+    deep_links: Arc<DeepLinks>,
 ) -> ResponseResult<()> {
+    let language = msg.from.as_ref().map(user_language).unwrap_or_default();
     match chat_type.as_str() {
         "a private chat" => {
-            let _ = bot.send_message(
-                msg.chat.id,
-                format!("/settings command is not supported in this chat type."),
-            );
+            let _ = bot
+                .send_message(msg.chat.id, language.text("settings-unsupported-chat"))
+                .await;
         }
-        "a group" | "a supergroup" => {
-            if let Some(user) = msg.from() {
-                // Initialize database connection
-                let pool = get_conn_pool();
-                // Create UserInfo struct from user data
-                let user_info = UserInfo {
-                    user_id: user.id.to_string(),
-                    username: user.username.clone(),
-                    first_name: Some(user.first_name.clone()),
-                    last_name: Some(user.last_name.clone().unwrap_or_default()),
-                };
-
-                // Save user info to database
-                match save_user_info(&pool, user_info).await {
-                    Ok(_) => log::info!("User info saved successfully for user_id: {}", user.id),
-                    Err(e) => log::error!("Failed to save user info: {}", e),
+        "a group" | "a supergroup" => match (&msg.sender_chat, msg.from.as_ref()) {
+            // An anonymous admin posts as the group itself, who they are is
+            // only known once they tap a button
+            (Some(sender_chat), _) if sender_chat.id == msg.chat.id => {
+                let keyboard =
+                    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+                        language.text("anonymous-admin-button"),
+                        "verify_admin",
+                    )]]);
+                let _ = bot
+                    .send_message(msg.chat.id, language.text("anonymous-admin"))
+                    .reply_markup(keyboard)
+                    .await;
+            }
+            // Posted on behalf of a channel
+            (Some(_), _) => {
+                let _ = bot
+                    .send_message(msg.chat.id, language.text("not-admin"))
+                    .await;
+            }
+            (None, Some(user)) => {
+                if is_group_admin(&bot, msg.chat.id, user.id).await {
+                    let _ = offer_settings(
+                        bot,
+                        user,
+                        msg.chat.id,
+                        bot_username,
+                        storage.as_ref(),
+                        &deep_links,
+                    )
+                    .await;
+                } else {
+                    let _ = bot
+                        .send_message(msg.chat.id, language.text("not-admin"))
+                        .await;
                 }
-
-                //Update setting_opts_arc with user_id and group_chat_id
-                setting_opts_arc.write().await.user_id = user.id.to_string();
-                setting_opts_arc.write().await.group_chat_id = msg.chat.id.to_string();
-
-                let sender_name = user
-                    .username
-                    .clone()
-                    .unwrap_or_else(|| user.first_name.clone());
-
-                let _ = start_settings(bot, msg.chat.id, sender_name, bot_username).await;
-            } else {
+            }
+            (None, None) => {
                 log::warn!("No user information found in message");
                 let _ = bot
-                    .send_message(msg.chat.id, "Could not process user information")
+                    .send_message(msg.chat.id, language.text("settings-no-user"))
                     .await;
             }
-        }
+        },
         _ => {
-            let _ = bot.send_message(msg.chat.id, format!("This bot helps you to read Apechain token buy information. Type /help for more information")).await;
+            let _ = bot
+                .send_message(msg.chat.id, language.text("settings-private-chat"))
+                .await;
+        }
+    }
+    Ok(())
+}
+
+/// Lists the group's tokens. In the group, admins also get the button to the
+/// private chat, where the list can be managed.
+async fn tokens_command(
+    bot: Bot,
+    msg: Message,
+    bot_username: String,
+    chat_type: String,
+    sessions: Arc<SessionStore>,
+    storage: Arc<dyn Storage>,
+    deep_links: Arc<DeepLinks>,
+) -> ResponseResult<()> {
+    let language = msg.from.as_ref().map(user_language).unwrap_or_default();
+    match chat_type.as_str() {
+        "a private chat" => {
+            let Some(user) = msg.from.as_ref() else {
+                return Ok(());
+            };
+            let user_id = user.id.to_string();
+            let Some(draft) = sessions.get(&user_id).await else {
+                return no_session(bot, msg.chat.id, language).await;
+            };
+            let language = Language::of(&draft);
+            if !is_group_admin(&bot, group_chat_id_of(&draft.group_chat_id), user.id).await {
+                return refuse_non_admin(bot, msg.chat.id, &user_id, &sessions, language).await;
+            }
+            let tokens = group_tokens(storage.as_ref(), &draft.group_chat_id).await;
+            token_menu(bot, Screen::new_message(msg.chat.id), &tokens, language).await
+        }
+        "a group" | "a supergroup" => {
+            let tokens = group_tokens(storage.as_ref(), &msg.chat.id.to_string()).await;
+            // The group speaks the language its tokens were set up in
+            let group_language = tokens.first().map_or(language, Language::of);
+            bot.send_message(msg.chat.id, tokens_text(&tokens, group_language))
+                .await?;
+            settings_command(bot, msg, bot_username, chat_type, storage, deep_links).await
+        }
+        _ => settings_command(bot, msg, bot_username, chat_type, storage, deep_links).await,
+    }
+}
+
+/// Posts the button leading an admin of the group to the private chat.
+async fn offer_settings(
+    bot: Bot,
+    user: &User,
+    group_chat_id: ChatId,
+    bot_username: String,
+    storage: &dyn Storage,
+    deep_links: &DeepLinks,
+) -> ResponseResult<()> {
+    let language = user_language(user);
+    // Create UserInfo struct from user data
+    let user_info = UserInfo {
+        user_id: user.id.to_string(),
+        username: user.username.clone(),
+        first_name: Some(user.first_name.clone()),
+        last_name: Some(user.last_name.clone().unwrap_or_default()),
+    };
+
+    // Save user info to database
+    match storage.save_user_info(user_info).await {
+        Ok(_) => log::info!("User info saved successfully for user_id: {}", user.id),
+        Err(e) => log::error!("Failed to save user info: {}", e),
+    }
+
+    let sender_name = user
+        .username
+        .clone()
+        .unwrap_or_else(|| user.first_name.clone());
+    let payload = deep_links.sign(group_chat_id.0, user.id.0, unix_now());
+
+    start_settings(
+        bot,
+        group_chat_id,
+        sender_name,
+        bot_username,
+        &payload,
+        language,
+    )
+    .await
+}
+
+/// Opens a settings session for the user and group, the draft speaking the
+/// user's language until a token is picked.
+async fn open_session(sessions: &SessionStore, user: &User, group_chat_id: ChatId) {
+    let user_id = user.id.to_string();
+    sessions.open(&user_id, &group_chat_id.to_string()).await;
+    sessions
+        .update(&user_id, |opts| {
+            if opts.language.is_empty() {
+                opts.language = user_language(user).code().to_string();
+            }
+        })
+        .await;
+}
+
+/// Whether the user is the creator or an administrator of the group right
+/// now. Errors, e.g. when the bot was removed from the group, count as no.
+async fn is_group_admin(bot: &Bot, group_chat_id: ChatId, user_id: UserId) -> bool {
+    match bot.get_chat_member(group_chat_id, user_id).await {
+        Ok(member) => member.is_privileged(),
+        Err(e) => {
+            error!(
+                "Could not check whether {} is an admin of {}: {}",
+                user_id, group_chat_id, e
+            );
+            false
         }
     }
+}
+
+/// Ends the session of a user who is no longer an admin of its group.
+async fn refuse_non_admin(
+    bot: Bot,
+    chat_id: ChatId,
+    user_id: &str,
+    sessions: &SessionStore,
+    language: Language,
+) -> ResponseResult<()> {
+    sessions.close(user_id).await;
+    bot.send_message(chat_id, language.text("not-admin"))
+        .await?;
+
     Ok(())
 }
 
+/// An anonymous admin tapped the button under their `/settings`, which tells
+/// who they are.
+async fn verify_anonymous_admin(
+    bot: Bot,
+    callback_id: String,
+    user: &User,
+    group_chat_id: ChatId,
+    storage: &dyn Storage,
+    deep_links: &DeepLinks,
+) -> ResponseResult<()> {
+    if !is_group_admin(&bot, group_chat_id, user.id).await {
+        bot.answer_callback_query(callback_id)
+            .text(user_language(user).text("not-admin"))
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    }
+    bot.answer_callback_query(callback_id).await?;
+    offer_settings(bot, user, group_chat_id, String::new(), storage, deep_links).await
+}
+
 async fn start_settings(
     bot: Bot,
     chat_id: ChatId,
     username: String,
     bot_username: String,
+    payload: &str,
+    language: Language,
 ) -> ResponseResult<()> {
     let bot_name = std::env::var("BOT_USERNAME").unwrap_or_default();
     let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::url(
-        "Configure Settings",
+        language.text("settings-open-button"),
         if bot_username.is_empty() {
-            format!("https://t.me/{}?start=available", bot_name)
+            format!("https://t.me/{}?start={}", bot_name, payload)
                 .parse()
                 .unwrap()
         } else {
-            format!("https://t.me/{}?start=available", bot_username)
+            format!("https://t.me/{}?start={}", bot_username, payload)
                 .parse()
                 .unwrap()
         },
@@ -196,7 +510,7 @@ async fn start_settings(
 
     bot.send_message(
         chat_id,
-        format!("@{}, to configure settings, please click the button below and then start a private chat with me if you haven't already.", username)
+        language.text_with("settings-open", &[("username", &username)]),
     )
     .reply_markup(keyboard)
     .await?;
@@ -204,985 +518,1462 @@ async fn start_settings(
     Ok(())
 }
 
-async fn start_command(bot: Bot, msg: Message, availability: String) -> ResponseResult<()> {
-    match availability.as_str() {
-        "available" => {
-            let _ = start(bot, msg.chat.id).await;
+/// Opens the settings session for the group the signed `payload` was made
+/// for.
+async fn start_command(
+    bot: Bot,
+    msg: Message,
+    payload: String,
+    sessions: Arc<SessionStore>,
+    storage: Arc<dyn Storage>,
+    deep_links: Arc<DeepLinks>,
+    dialogue_storage: Arc<DialogueStorage>,
+) -> ResponseResult<()> {
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+    let language = user_language(user);
+    let group_chat_id = match deep_links.verify(&payload, user.id.0, unix_now()) {
+        Ok(group_chat_id) => ChatId(group_chat_id),
+        Err(e) => {
+            log::info!("Refused the settings link of user {}: {}", user.id, e);
+            bot.send_message(msg.chat.id, language.text("settings-link-invalid"))
+                .await?;
+            return Ok(());
         }
-        _ => {}
+    };
+    if !is_group_admin(&bot, group_chat_id, user.id).await {
+        bot.send_message(msg.chat.id, language.text("not-admin"))
+            .await?;
+        return Ok(());
     }
-    Ok(())
-}
 
-async fn start(bot: Bot, chat_id: ChatId) -> ResponseResult<()> {
-    let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
-        "Enter Token Address",
-        "token_address",
-    )]]);
+    open_session(&sessions, user, group_chat_id).await;
+    // A prompt of the previous session is not answered any more
+    let _ = SettingsDialogue::new(dialogue_storage, msg.chat.id)
+        .exit()
+        .await;
+    let language = sessions
+        .get(&user.id.to_string())
+        .await
+        .map_or(language, |draft| Language::of(&draft));
+    // A group that tracks tokens already picks one, any other adds its first
+    let tokens = group_tokens(storage.as_ref(), &group_chat_id.to_string()).await;
+    let screen = Screen::new_message(msg.chat.id);
+    if tokens.is_empty() {
+        start(bot, screen, language).await
+    } else {
+        token_menu(bot, screen, &tokens, language).await
+    }
+}
 
-    // First message with keyboard
-    bot.send_message(
-        chat_id,
-        "Please click the button below to enter a token address",
+async fn start(bot: Bot, screen: Screen, language: Language) -> ResponseResult<()> {
+    let mut rows = CHAINS
+        .iter()
+        .map(|chain| {
+            vec![InlineKeyboardButton::callback(
+                chain.name,
+                format!("chain_{}", chain.id),
+            )]
+        })
+        .collect::<Vec<_>>();
+    rows.push(vec![InlineKeyboardButton::callback(
+        language.text("button-back"),
+        "tokens",
+    )]);
+
+    show(
+        &bot,
+        screen,
+        language.text("choose-chain"),
+        InlineKeyboardMarkup::new(rows),
     )
-    .reply_markup(keyboard)
     .await?;
 
     Ok(())
 }
 
-async fn answer_button(
-    bot: Bot,
-    callback: CallbackQuery,
-    setting_opts_arc: Arc<RwLock<SettingOpts>>,
-) -> ResponseResult<()> {
-    match callback.data {
-        Some(callback_string) => {
-            // println!("callback query:  {}", callback_string);
-            match callback_string.as_str() {
-                "token_address" => {
-                    let _ = message_by_callback(
-                        bot,
-                        callback.from.id.into(),
-                        "token_address".to_string(),
-                    )
-                    .await;
-                }
-                "min_buy_amount" => {
-                    let _ = message_by_callback(
-                        bot,
-                        callback.from.id.into(),
-                        "min_buy_amount".to_string(),
-                    )
-                    .await;
-                }
-                "buy_step" => {
-                    let _ =
-                        message_by_callback(bot, callback.from.id.into(), "buy_step".to_string())
-                            .await;
-                }
-                "emoji" => {
-                    let _ = message_by_callback(bot, callback.from.id.into(), "emoji".to_string())
-                        .await;
-                }
-                "media_toggle" => {
-                    let _ = media_toggle(bot, callback.from.id.into(), setting_opts_arc).await;
-                }
-                "add_media" => {
-                    let _ = select_media_type(bot, callback.from.id.into()).await;
-                }
-                "tg_link" => {
-                    let _ =
-                        message_by_callback(bot, callback.from.id.into(), "tg_link".to_string())
-                            .await;
-                }
-                "website_link" => {
-                    let _ = message_by_callback(
-                        bot,
-                        callback.from.id.into(),
-                        "website_link".to_string(),
-                    )
-                    .await;
-                }
-                "twitter_link" => {
-                    let _ = message_by_callback(
-                        bot,
-                        callback.from.id.into(),
-                        "twitter_link".to_string(),
-                    )
-                    .await;
-                }
-                "delete_token" => {
-                    let _ = delete_and_back_to_new_token(
-                        bot,
-                        callback.from.id.into(),
-                        setting_opts_arc,
-                    )
-                    .await;
-                }
-                "photo" => {
-                    let _ = add_media(
-                        bot,
-                        callback.from.id.into(),
-                        setting_opts_arc,
-                        "photo".to_string(),
-                    )
-                    .await;
-                }
-                "video" => {
-                    let _ = add_media(
-                        bot,
-                        callback.from.id.into(),
-                        setting_opts_arc,
-                        "video".to_string(),
-                    )
-                    .await;
-                }
-                _ => {
-                    log::warn!(
-                        "Received callback {} which isn't implemented.",
-                        callback_string
-                    );
-                }
-            }
-        }
-        None => {}
-    };
-    Ok(())
-}
-
-async fn message_by_callback(
-    bot: Bot,
-    chat_id: ChatId,
-    callback_string: String,
-) -> ResponseResult<()> {
-    bot.send_message(chat_id, format!("{}", callback_string))
-        .reply_markup(ReplyMarkup::ForceReply(
-            ForceReply::new()
-                .selective()
-                // .input_field_placeholder("0x...".to_string()),
-        ))
+async fn no_session(bot: Bot, chat_id: ChatId, language: Language) -> ResponseResult<()> {
+    bot.send_message(chat_id, language.text("no-session"))
         .await?;
 
     Ok(())
 }
 
-async fn media_toggle(
-    bot: Bot,
-    chat_id: ChatId,
-    setting_opts_arc: Arc<RwLock<SettingOpts>>,
-) -> ResponseResult<()> {
-    let pool = get_conn_pool();
-    let toogle_value = !(setting_opts_arc.read().await.media_toggle);
-    setting_opts_arc.write().await.media_toggle = toogle_value;
-    let _ = save_setting_opts_db(&pool, setting_opts_arc.read().await.clone()).await;
-
-    setting_option(
-        bot.clone(),
-        chat_id,
-        "🎉 Media toggle option is saved. Now you can adjust the other settings:".to_string(),
-        setting_opts_arc.read().await.clone(),
-    )
-    .await?;
-    Ok(())
+/// The user's Telegram language if the bot speaks it, for texts sent before a
+/// group is picked.
+fn user_language(user: &User) -> Language {
+    user.language_code
+        .as_deref()
+        .and_then(Language::from_code)
+        .unwrap_or_default()
 }
 
-async fn select_media_type(bot: Bot, chat_id: ChatId) -> ResponseResult<()> {
-    let keyboard = InlineKeyboardMarkup::new(vec![
-        vec![InlineKeyboardButton::callback("Photo", "photo")],
-        vec![InlineKeyboardButton::callback("Video", "video")],
-    ]);
+/// Where a menu is shown: over the message whose button was pressed, or as a
+/// new message after a command or a typed answer.
+#[derive(Clone, Copy)]
+struct Screen {
+    chat_id: ChatId,
+    message_id: Option<MessageId>,
+}
 
-    // First message with keyboard
-    bot.send_message(chat_id, "Please choose the type of media.")
-        .reply_markup(keyboard)
-        .await?;
+impl Screen {
+    fn new_message(chat_id: ChatId) -> Self {
+        Self {
+            chat_id,
+            message_id: None,
+        }
+    }
 
-    Ok(())
+    fn of(callback: &CallbackQuery) -> Self {
+        match &callback.message {
+            Some(message) => Self {
+                chat_id: message.chat().id,
+                message_id: Some(message.id()),
+            },
+            None => Self::new_message(callback.from.id.into()),
+        }
+    }
 }
 
-async fn add_media(
-    bot: Bot,
-    chat_id: ChatId,
-    setting_opts_arc: Arc<RwLock<SettingOpts>>,
-    callback_string: String,
-) -> ResponseResult<()> {
-    let pool = get_conn_pool();
-    setting_opts_arc.write().await.media_type = callback_string.to_string();
-    let _ = save_setting_opts_db(&pool, setting_opts_arc.read().await.clone()).await;
-    bot.send_message(chat_id, format!("{}", callback_string))
-        .reply_markup(ReplyMarkup::ForceReply(
-            ForceReply::new(), // .input_field_placeholder(Some("0x...".to_string()))
-        ))
+/// Shows a menu and returns the message it is in. A message that cannot be
+/// edited any more, e.g. because it was deleted, is replaced by a new one.
+async fn show(
+    bot: &Bot,
+    screen: Screen,
+    text: String,
+    keyboard: InlineKeyboardMarkup,
+) -> ResponseResult<MessageId> {
+    if let Some(message_id) = screen.message_id {
+        match bot
+            .edit_message_text(screen.chat_id, message_id, text.clone())
+            .reply_markup(keyboard.clone())
+            .await
+        {
+            Ok(_) | Err(RequestError::Api(ApiError::MessageNotModified)) => return Ok(message_id),
+            Err(RequestError::Api(e)) => {
+                log::info!("Could not edit message {}: {}", message_id, e)
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    let message = bot
+        .send_message(screen.chat_id, text)
+        .reply_markup(keyboard)
         .await?;
 
-    Ok(())
+    Ok(message.id)
 }
 
-async fn answer_replyed_message(
-    bot: Bot,
-    msg: Message,
-    setting_opts_arc: Arc<RwLock<SettingOpts>>,
-) -> ResponseResult<()> {
-    let pool = get_conn_pool();
-    let user_id = msg.from().unwrap().id.to_string();
-    let chat_id = msg.chat.id;
-    let reply_text = msg.reply_to_message().and_then(|reply| reply.text());
-
-    if msg.photo().is_some() {
-        if reply_text == Some("photo") {
-            if let Some(latest_photo) = msg.photo().iter().last() {
-                setting_opts_arc.write().await.media_file_id =
-                    Some(latest_photo[0].file.id.clone());
-
-                // Update the settings
-                let _ = save_setting_opts_db(&pool, setting_opts_arc.read().await.clone()).await;
+/// The settings panel and its sub-menus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Menu {
+    Main,
+    Thresholds,
+    Media,
+    Links,
+}
 
-                setting_option(
-                    bot.clone(),
-                    chat_id,
-                    "🎉 Photo saved. Now you can adjust other settings:".to_string(),
-                    setting_opts_arc.read().await.clone(),
-                )
-                .await?;
-                return Ok(());
-            }
-        } else {
-            // let selected_setting_opt = setting_opts_wrapper.get_selected_setting_opt().await;
-            setting_option(
-                bot.clone(),
-                chat_id,
-                "❌ Invalid photo style. Please again".to_string(),
-                setting_opts_arc.read().await.clone(),
-            )
-            .await?;
-            return Ok(());
+impl Menu {
+    fn from_callback(callback_string: &str) -> Option<Self> {
+        match callback_string {
+            "menu_main" => Some(Self::Main),
+            "menu_thresholds" => Some(Self::Thresholds),
+            "menu_media" => Some(Self::Media),
+            "menu_links" => Some(Self::Links),
+            _ => None,
         }
-    } else if msg.video().is_some() {
-        if reply_text == Some("video") {
-            if let Some(latest_video) = msg.video().iter().last() {
-                setting_opts_arc.write().await.media_file_id = Some(latest_video.file.id.clone());
+    }
 
-                // Update the settings
-                let _ = save_setting_opts_db(&pool, setting_opts_arc.read().await.clone()).await;
+    fn callback(self) -> &'static str {
+        match self {
+            Self::Main => "menu_main",
+            Self::Thresholds => "menu_thresholds",
+            Self::Media => "menu_media",
+            Self::Links => "menu_links",
+        }
+    }
 
-                setting_option(
-                    bot.clone(),
-                    chat_id,
-                    "🎉 Video saved. Now you can adjust the other settings:".to_string(),
-                    setting_opts_arc.read().await.clone(),
-                )
-                .await?;
-                return Ok(());
+    /// The menu with the button of a setting.
+    fn of(setting: &Setting) -> Self {
+        match setting {
+            Setting::MinBuyAmount | Setting::BuyStep | Setting::MinSellAmount => Self::Thresholds,
+            Setting::Photo | Setting::Video | Setting::SellPhoto | Setting::SellVideo => {
+                Self::Media
             }
-        } else {
-            setting_option(
-                bot.clone(),
-                chat_id,
-                "❌ Invalid video style. Please again".to_string(),
-                setting_opts_arc.read().await.clone(),
-            )
-            .await?;
-            return Ok(());
+            Setting::TgLink | Setting::WebsiteLink | Setting::TwitterLink => Self::Links,
+            Setting::TokenAddress { .. }
+            | Setting::Emoji
+            | Setting::SellEmoji
+            | Setting::BuyTemplate => Self::Main,
         }
-    } else if let Some(text) = msg.text() {
-        // if text.starts_with("0x") {
-        //     if text.len() == 42 {
-        //         if reply_text == Some("token_address") {
-        //             let group_chat_id = setting_opts_arc.read().await.group_chat_id.clone();
-        //             let existing_settings = get_setting_opt(&pool, user_id.to_string().clone(), group_chat_id.clone(), text.to_string()).await.unwrap();
-        //             // println!("Found existing settings: {:?}", existing_settings.clone());
-        //             *setting_opts_arc.write().await = existing_settings.clone();
-        //             let _ = save_setting_opts_db(&pool, setting_opts_arc.read().await.clone()).await;
-
-        //             setting_option(bot.clone(), chat_id, "🎉 Token address saved. Now you can adjust the other settings:".to_string(), setting_opts_arc.read().await.clone()).await?;
-        //             let _ = confirm_style_change(bot.clone(), setting_opts_arc.read().await.clone()).await;
-
-        //         } else{
-        //             bot.send_message(
-        //                 chat_id,
-        //                 format!("❌ Token address is not valid. Try again")
-        //             ).await?;
-        //             message_by_callback(bot.clone(), chat_id, "token_address".to_string()).await?;
-        //         }
-        //     }
-        // }
-        // else
-        if let Some(reply_text) = reply_text {
-            let mut head_text = "";
-            match reply_text {
-                "token_address" => {
-                    if is_token_address(text) {
-                        let group_chat_id = setting_opts_arc.read().await.group_chat_id.clone();
-                        let existing_settings = get_setting_opt(
-                            &pool,
-                            user_id.to_string().clone(),
-                            group_chat_id.clone(),
-                            text.to_string(),
-                        )
-                        .await
-                        .unwrap();
-                        // println!("Found existing settings: {:?}", existing_settings.clone());
-                        *setting_opts_arc.write().await = existing_settings.clone();
-                        // let _ = save_setting_opts_db(&pool, setting_opts_arc.read().await.clone()).await;
-                        head_text =
-                            "🎉 Token address saved. Now you can adjust the other settings:";
-
-                        // setting_option(bot.clone(), chat_id, "🎉 Token address saved. Now you can adjust the other settings:".to_string(), setting_opts_arc.read().await.clone()).await?;
-                        let _ = confirm_style_change(
-                            bot.clone(),
-                            setting_opts_arc.read().await.clone(),
-                        )
-                        .await;
-                    } else {
-                        bot.send_message(
-                            chat_id,
-                            format!("❌ Token address is not valid. Try again"),
-                        )
-                        .await?;
-                        message_by_callback(bot.clone(), chat_id, "token_address".to_string())
-                            .await?;
-                        return Ok(());
-                    }
-                }
-                "min_buy_amount" => {
-                    if let Ok(amount) = text.parse::<f64>() {
-                        setting_opts_arc.write().await.min_buy_amount = amount;
-                        head_text =
-                            "🎉 Min buy amount saved. Now you can adjust the other settings:";
-                    } else {
-                        head_text = "❌ Min buy amount is not valid. Please try again.";
-                    }
-                }
-                "buy_step" => {
-                    if let Ok(step) = text.parse::<i32>() {
-                        setting_opts_arc.write().await.buy_step = step;
-                        head_text = "🎉 Buy step saved. Now you can adjust the other settings:";
-                    } else {
-                        head_text = "❌ Buy step is not valid. Please try again.";
-                    }
-                }
-                "emoji" => {
-                    if is_emoji(text) {
-                        setting_opts_arc.write().await.emoji = text.to_string();
-                        head_text = "🎉 Emoji saved. Now you can adjust the other settings:";
-                    } else {
-                        head_text = "❌ Emoji is not valid. Please try again.";
-                    }
-                }
-                "tg_link" => {
-                    if is_tg_link(text) {
-                        setting_opts_arc.write().await.tg_link = text.to_string();
-                        head_text = "🎉 Tg link saved. Now you can adjust the other settings:";
-                    } else {
-                        head_text = "❌ Tg link is not valid. Please try again.";
-                    }
-                }
-                "website_link" => {
-                    if is_website_link(text) {
-                        setting_opts_arc.write().await.website_link = text.to_string();
-                        head_text = "🎉 Website link saved. Now you can adjust the other settings:";
-                    } else {
-                        head_text = "❌ Website link is not valid. Please try again.";
-                    }
-                }
-                "twitter_link" => {
-                    if is_twitter_link(text) {
-                        setting_opts_arc.write().await.twitter_link = text.to_string();
-                        head_text = "🎉 Twitter link saved. Now you can adjust the other settings:";
-                    } else {
-                        head_text = "❌ Twitter link is not valid. Please try again.";
-                    }
-                }
+    }
+}
 
-                _ => log::warn!("Unhandled reply type: {}", reply_text),
+/// Every button is acknowledged, with a toast when something went wrong.
+async fn answer_button(
+    bot: Bot,
+    callback: CallbackQuery,
+    sessions: Arc<SessionStore>,
+    storage: Arc<dyn Storage>,
+    watchers: Arc<WatcherRegistry>,
+    deep_links: Arc<DeepLinks>,
+    dialogue: SettingsDialogue,
+) -> ResponseResult<()> {
+    let Some(callback_string) = callback.data.clone() else {
+        bot.answer_callback_query(callback.id).await?;
+        return Ok(());
+    };
+    let user_id = callback.from.id.to_string();
+    if callback_string == "verify_admin" {
+        let Some(group_chat_id) = callback.message.as_ref().map(|message| message.chat().id) else {
+            bot.answer_callback_query(callback.id).await?;
+            return Ok(());
+        };
+        return verify_anonymous_admin(
+            bot,
+            callback.id,
+            &callback.from,
+            group_chat_id,
+            storage.as_ref(),
+            &deep_links,
+        )
+        .await;
+    }
+    let Some(draft) = sessions.get(&user_id).await else {
+        bot.answer_callback_query(callback.id)
+            .text(user_language(&callback.from).text("no-session"))
+            .await?;
+        return Ok(());
+    };
+    let language = Language::of(&draft);
+    // Any button drops the pending prompt, the ones asking for a setting
+    // start a new one
+    let _ = dialogue.exit().await;
+    if !is_group_admin(
+        &bot,
+        group_chat_id_of(&draft.group_chat_id),
+        callback.from.id,
+    )
+    .await
+    {
+        sessions.close(&user_id).await;
+        bot.answer_callback_query(callback.id)
+            .text(language.text("not-admin"))
+            .await?;
+        return Ok(());
+    }
+    let screen = Screen::of(&callback);
+    let draft = match refresh_draft(&sessions, storage.as_ref(), &user_id).await {
+        Ok(refreshed) => refreshed.unwrap_or(draft),
+        Err(token_address) => {
+            let tokens = group_tokens(storage.as_ref(), &draft.group_chat_id).await;
+            if let Err(e) = token_menu(bot.clone(), screen, &tokens, language).await {
+                error!("Failed to show the tokens: {}", e);
+            }
+            bot.answer_callback_query(callback.id)
+                .text(language.text_with("token-not-found", &[("token", &token_address)]))
+                .await?;
+            return Ok(());
+        }
+    };
+    let language = Language::of(&draft);
+
+    let toast: ResponseResult<Option<String>> = async {
+        match callback_string.as_str() {
+            menu_callback if menu_callback.starts_with("menu_") => {
+                let menu = Menu::from_callback(menu_callback).unwrap_or(Menu::Main);
+                let head_text =
+                    language.text_with("editing-token", &[("token", &draft.token_address)]);
+                settings_panel(bot.clone(), screen, head_text, draft.clone(), menu).await?;
+            }
+            "min_buy_amount" | "buy_step" | "emoji" | "tg_link" | "website_link"
+            | "twitter_link" | "min_sell_amount" | "sell_emoji" => {
+                if let Some(setting) = Setting::from_callback(&callback_string) {
+                    let text = language.prompt(setting.field());
+                    let prompt = Prompt::new(&draft, setting);
+                    ask_setting(bot.clone(), screen, &dialogue, prompt, text, language).await?;
+                }
             }
+            chain_callback if chain_callback.starts_with("chain_") => {
+                select_chain(
+                    bot.clone(),
+                    screen,
+                    chain_id_of(chain_callback),
+                    &user_id,
+                    &sessions,
+                    &dialogue,
+                    language,
+                )
+                .await?;
+            }
+            "media_toggle" => {
+                media_toggle(
+                    bot.clone(),
+                    screen,
+                    &user_id,
+                    &sessions,
+                    storage.as_ref(),
+                    &watchers,
+                    language,
+                )
+                .await?;
+            }
+            "sell_alerts" => {
+                sell_alerts_toggle(
+                    bot.clone(),
+                    screen,
+                    &user_id,
+                    &sessions,
+                    storage.as_ref(),
+                    &watchers,
+                    language,
+                )
+                .await?;
+            }
+            "buy_template" => {
+                edit_buy_template(bot.clone(), screen, &draft, &dialogue, language).await?;
+            }
+            "language" => {
+                select_language(bot.clone(), screen, language).await?;
+            }
+            language_callback if language_callback.starts_with("language_") => {
+                set_language(
+                    bot.clone(),
+                    screen,
+                    &user_id,
+                    &sessions,
+                    storage.as_ref(),
+                    &watchers,
+                    language_callback.trim_start_matches("language_"),
+                )
+                .await?;
+            }
+            "add_media" => {
+                select_media_type(bot.clone(), screen, "", language).await?;
+            }
+            "add_sell_media" => {
+                select_media_type(bot.clone(), screen, "sell_", language).await?;
+            }
+            "tokens" => {
+                let tokens = group_tokens(storage.as_ref(), &draft.group_chat_id).await;
+                token_menu(bot.clone(), screen, &tokens, language).await?;
+            }
+            "add_token" => {
+                add_token(bot.clone(), screen, &user_id, &sessions, language).await?;
+            }
+            edit_callback if edit_callback.starts_with("edit_token_") => {
+                return edit_token(
+                    bot.clone(),
+                    screen,
+                    &user_id,
+                    &sessions,
+                    storage.as_ref(),
+                    edit_callback.trim_start_matches("edit_token_"),
+                )
+                .await;
+            }
+            "delete_token" => {
+                confirm_removal(
+                    bot.clone(),
+                    screen,
+                    &draft.token_address,
+                    Menu::Main.callback(),
+                    language,
+                )
+                .await?;
+            }
+            remove_callback if remove_callback.starts_with("remove_token_") => {
+                let token_address = remove_callback.trim_start_matches("remove_token_");
+                confirm_removal(bot.clone(), screen, token_address, "tokens", language).await?;
+            }
+            confirm_callback if confirm_callback.starts_with("confirm_remove_") => {
+                return remove_token(
+                    bot.clone(),
+                    screen,
+                    &user_id,
+                    &sessions,
+                    storage.as_ref(),
+                    &watchers,
+                    confirm_callback.trim_start_matches("confirm_remove_"),
+                )
+                .await;
+            }
+            "photo" | "video" | "sell_photo" | "sell_video" => {
+                let Some(setting) = Setting::from_callback(&callback_string) else {
+                    return Ok(None);
+                };
+                let Some(setting_opts) = set_media_type(
+                    &user_id,
+                    &sessions,
+                    storage.as_ref(),
+                    &watchers,
+                    &callback_string,
+                )
+                .await
+                else {
+                    return Ok(Some(language.text("no-session")));
+                };
+                let text = language.prompt(setting.field());
+                let prompt = Prompt::new(&setting_opts, setting);
+                ask_setting(bot.clone(), screen, &dialogue, prompt, text, language).await?;
+            }
+            _ => {
+                log::warn!(
+                    "Received callback {} which isn't implemented.",
+                    callback_string
+                );
+            }
+        }
+        Ok(None)
+    }
+    .await;
 
-            let _ = save_setting_opts_db(&pool, setting_opts_arc.read().await.clone()).await;
-            // Now we can use selected_setting_opt for the JSON message
-            // bot.send_message(
-            //     chat_id,
-            //     format!("The following data about setting option is saved on the server: \n\
-            //     \n\
-            //     ```\
-            //     {}\
-            //     ```\
-            //     ", serde_json::to_string_pretty(&selected_setting_opt).unwrap()
-            //     )
-            // )
-            // .parse_mode(MarkdownV2)
-            // .await?;
+    let toast = toast.unwrap_or_else(|e| {
+        error!("Failed to answer button {}: {}", callback_string, e);
+        Some(language.text("button-failed"))
+    });
+    let mut answer = bot.answer_callback_query(callback.id);
+    if let Some(toast) = toast {
+        answer = answer.text(toast);
+    }
+    answer.await?;
 
-            setting_option(
-                bot.clone(),
-                chat_id,
-                head_text.to_string(),
-                setting_opts_arc.read().await.clone(),
-            )
-            .await?;
-        }
+    Ok(())
+}
+
+fn group_chat_id_of(group_chat_id: &str) -> ChatId {
+    ChatId(group_chat_id.parse().unwrap_or_default())
+}
+
+fn chain_id_of(callback_string: &str) -> &str {
+    callback_string.trim_start_matches("chain_")
+}
+
+/// Remembers the chosen chain in the draft and asks for the token address.
+async fn select_chain(
+    bot: Bot,
+    screen: Screen,
+    chain_id: &str,
+    user_id: &str,
+    sessions: &SessionStore,
+    dialogue: &SettingsDialogue,
+    language: Language,
+) -> ResponseResult<()> {
+    let Some(chain) = find_chain(chain_id) else {
+        log::warn!("Received unknown chain {}", chain_id);
+        return Ok(());
+    };
+    // The token is asked for next, whatever the draft held before
+    let Some(draft) = sessions
+        .update(user_id, |opts| {
+            opts.token_address.clear();
+            opts.chain_id = chain.id.to_string();
+        })
+        .await
+    else {
+        return no_session(bot, screen.chat_id, language).await;
+    };
+    let setting = Setting::TokenAddress {
+        chain_id: draft.chain_id.clone(),
+    };
+    let text = language.prompt(setting.field());
+    ask_setting(
+        bot,
+        screen,
+        dialogue,
+        Prompt::new(&draft, setting),
+        text,
+        language,
+    )
+    .await
+}
+
+/// Shows the current template and the placeholders, then asks for the new
+/// template.
+async fn edit_buy_template(
+    bot: Bot,
+    screen: Screen,
+    draft: &SettingOpts,
+    dialogue: &SettingsDialogue,
+    language: Language,
+) -> ResponseResult<()> {
+    let current = draft
+        .buy_template
+        .clone()
+        .unwrap_or_else(|| default_buy_template(language));
+    let text = format!(
+        "{}\n\n{}",
+        language.text_with(
+            "template-editor",
+            &[
+                ("template", &current),
+                ("placeholders", &placeholder_help(language)),
+            ],
+        ),
+        language.prompt("buy_template")
+    );
+    let prompt = Prompt::new(draft, Setting::BuyTemplate);
+    ask_setting(bot, screen, dialogue, prompt, text, language).await
+}
+
+/// Shows the prompt for a setting with a button back to its menu. The next
+/// message in the chat is taken as the answer.
+async fn ask_setting(
+    bot: Bot,
+    screen: Screen,
+    dialogue: &SettingsDialogue,
+    prompt: Prompt,
+    text: String,
+    language: Language,
+) -> ResponseResult<()> {
+    let back = match prompt.setting {
+        Setting::TokenAddress { .. } => "tokens",
+        ref setting => Menu::of(setting).callback(),
+    };
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        language.text("button-back"),
+        back,
+    )]]);
+    let message_id = show(&bot, screen, text, keyboard).await?;
+
+    let prompt = Prompt {
+        message_id: Some(message_id.0),
+        ..prompt
+    };
+    if let Err(e) = dialogue.update(DialogueState::Awaiting(prompt)).await {
+        error!("Failed to save the dialogue: {}", e);
     }
-    // else if msg.document().is_some() {
-    // } else if msg.sticker().is_some() {
-    // } else {
-    // }
 
     Ok(())
 }
 
-async fn setting_option(
+async fn media_toggle(
     bot: Bot,
-    chat_id: ChatId,
-    head_text: String,
-    setting_opts: SettingOpts,
+    screen: Screen,
+    user_id: &str,
+    sessions: &SessionStore,
+    storage: &dyn Storage,
+    watchers: &WatcherRegistry,
+    language: Language,
+) -> ResponseResult<()> {
+    let Some(setting_opts) = sessions
+        .update(user_id, |opts| opts.media_toggle = !opts.media_toggle)
+        .await
+    else {
+        return no_session(bot, screen.chat_id, language).await;
+    };
+    let _ = storage.save_setting_opts(setting_opts.clone()).await;
+    apply_setting_opts(watchers, setting_opts.clone()).await;
+
+    settings_panel(
+        bot,
+        screen,
+        language.text("saved-media-toggle"),
+        setting_opts,
+        Menu::Media,
+    )
+    .await
+}
+
+async fn sell_alerts_toggle(
+    bot: Bot,
+    screen: Screen,
+    user_id: &str,
+    sessions: &SessionStore,
+    storage: &dyn Storage,
+    watchers: &WatcherRegistry,
+    language: Language,
+) -> ResponseResult<()> {
+    let Some(setting_opts) = sessions
+        .update(user_id, |opts| opts.sell_alerts = !opts.sell_alerts)
+        .await
+    else {
+        return no_session(bot, screen.chat_id, language).await;
+    };
+    let _ = storage.save_setting_opts(setting_opts.clone()).await;
+    apply_setting_opts(watchers, setting_opts.clone()).await;
+
+    settings_panel(
+        bot,
+        screen,
+        language.text("saved-sell-alerts"),
+        setting_opts,
+        Menu::Main,
+    )
+    .await
+}
+
+async fn select_language(bot: Bot, screen: Screen, language: Language) -> ResponseResult<()> {
+    let mut rows = Language::ALL
+        .iter()
+        .map(|option| {
+            vec![InlineKeyboardButton::callback(
+                option.name(),
+                format!("language_{}", option.code()),
+            )]
+        })
+        .collect::<Vec<_>>();
+    rows.push(vec![InlineKeyboardButton::callback(
+        language.text("button-back"),
+        Menu::Main.callback(),
+    )]);
+
+    show(
+        &bot,
+        screen,
+        language.text("choose-language"),
+        InlineKeyboardMarkup::new(rows),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Switches the group's settings keyboard and alerts to the picked language.
+async fn set_language(
+    bot: Bot,
+    screen: Screen,
+    user_id: &str,
+    sessions: &SessionStore,
+    storage: &dyn Storage,
+    watchers: &WatcherRegistry,
+    code: &str,
+) -> ResponseResult<()> {
+    let Some(picked) = Language::from_code(code) else {
+        log::warn!("Received unknown language {}", code);
+        return Ok(());
+    };
+    let Some(setting_opts) = sessions
+        .update(user_id, |opts| opts.language = picked.code().to_string())
+        .await
+    else {
+        return no_session(bot, screen.chat_id, picked).await;
+    };
+    let _ = storage.save_setting_opts(setting_opts.clone()).await;
+    apply_setting_opts(watchers, setting_opts.clone()).await;
+
+    settings_panel(
+        bot,
+        screen,
+        picked.text("saved-language"),
+        setting_opts,
+        Menu::Main,
+    )
+    .await
+}
+
+/// `prefix` is empty for buy media and `sell_` for sell media.
+async fn select_media_type(
+    bot: Bot,
+    screen: Screen,
+    prefix: &str,
+    language: Language,
 ) -> ResponseResult<()> {
     let keyboard = InlineKeyboardMarkup::new(vec![
         vec![InlineKeyboardButton::callback(
-            format!("Change minBuy: {}", setting_opts.min_buy_amount),
-            "min_buy_amount",
+            language.text("media-photo"),
+            format!("{}photo", prefix),
         )],
         vec![InlineKeyboardButton::callback(
-            format!("Change step: {}", setting_opts.buy_step),
-            "buy_step",
-        )],
-        vec![InlineKeyboardButton::callback(
-            format!("Change Emoji: {}", setting_opts.emoji),
-            "emoji",
-        )],
-        vec![InlineKeyboardButton::callback(
-            format!("Enable/Disable media: {}", setting_opts.media_toggle),
-            "media_toggle",
-        )],
-        vec![InlineKeyboardButton::callback(
-            format!(
-                "Add Media: {}",
-                setting_opts.media_file_id.clone().unwrap_or("".to_string())
-            ),
-            "add_media",
+            language.text("media-video"),
+            format!("{}video", prefix),
         )],
         vec![InlineKeyboardButton::callback(
-            format!("Change Tg Link: {}", setting_opts.tg_link),
-            "tg_link",
+            language.text("button-back"),
+            Menu::Media.callback(),
         )],
-        vec![InlineKeyboardButton::callback(
-            format!("Change Twitter Link: {}", setting_opts.twitter_link),
-            "twitter_link",
-        )],
-        vec![InlineKeyboardButton::callback(
-            format!("Change Website Link: {}", setting_opts.website_link),
-            "website_link",
-        )],
-        vec![InlineKeyboardButton::callback(
-            "Delete Token",
-            "delete_token",
-        )],
-        // vec![
-        //     // InlineKeyboardButton::callback("Confirm", "confirm"),
-        //     InlineKeyboardButton::url(
-        //         "Go back to group",
-        //         format!("https://t.me/c/{}", group_chat_id).parse().unwrap()
-        //     )
-        // ]
     ]);
 
-    // First message with keyboard
-    bot.send_message(chat_id, format!("{}", head_text))
-        .reply_markup(keyboard)
-        // .reply_markup(ReplyMarkup::ForceReply(
-        //     ForceReply::new()
-        // ))
-        .await?;
+    show(&bot, screen, language.text("choose-media-type"), keyboard).await?;
 
     Ok(())
 }
 
-async fn confirm_style_change(bot: Bot, setting_opts: SettingOpts) -> ResponseResult<()> {
-    let pool = get_conn_pool().clone();
-    let user_id = setting_opts.user_id;
-    let group_chat_id = setting_opts.group_chat_id;
-    let token_adr = setting_opts.token_address;
-    bot.send_message(
-        ChatId(group_chat_id.parse().expect("REASON")),
-        "Catching new buy transactions...",
-    )
-    .await?;
+/// Saves the picked media type, the file itself is asked for next.
+async fn set_media_type(
+    user_id: &str,
+    sessions: &SessionStore,
+    storage: &dyn Storage,
+    watchers: &WatcherRegistry,
+    callback_string: &str,
+) -> Option<SettingOpts> {
+    let setting_opts = sessions
+        .update(user_id, |opts| {
+            match callback_string.strip_prefix("sell_") {
+                Some(media_type) => opts.sell_media_type = media_type.to_string(),
+                None => opts.media_type = callback_string.to_string(),
+            }
+        })
+        .await?;
+    let _ = storage.save_setting_opts(setting_opts.clone()).await;
+    apply_setting_opts(watchers, setting_opts.clone()).await;
 
-    let request_client = Client::new();
-    let debank_api_key = std::env::var("DEBANK_API_KEY").unwrap();
-
-    let interval = tokio::time::interval(std::time::Duration::from_secs(5));
-    tokio::spawn(async move {
-        let mut interval = interval;
-        let mut flag_transaction_hash = String::new();
-        loop {
-            interval.tick().await;
-            if !token_adr.is_empty() && !user_id.is_empty() {
-                match get_token_transfers(request_client.clone(), &token_adr).await {
-                    Ok(token_transfer) => {
-                        if let Some(first_transfer) = token_transfer.items.first() {
-                            // println!("First transfer {}: {}", first_transfer.tx_hash.clone(), first_transfer.to.name.clone().unwrap_or_default());
-                            let transaction_hash = first_transfer.tx_hash.clone();
-                            let current_transaction_to_name =
-                                first_transfer.to.name.clone().unwrap_or_default();
-                            if flag_transaction_hash != transaction_hash
-                                && !current_transaction_to_name.is_empty()
-                            {
-                                flag_transaction_hash = transaction_hash;
-
-                                // println!("current_buy_transaction_to_name:  {}", current_transaction_to_name);
-                                //get setting options
-                                let selected_setting_opts = get_setting_opt(
-                                    &pool,
-                                    user_id.to_string(),
-                                    group_chat_id.to_string(),
-                                    token_adr.to_string(),
-                                )
-                                .await
-                                .unwrap();
-                                // let website_link = &setting_opts.website_link;
-                                let website_link = &selected_setting_opts.website_link;
-                                // let tg_link = &setting_opts.tg_link;
-                                let tg_link = &selected_setting_opts.tg_link;
-                                // let twitter_link = &setting_opts.twitter_link;
-                                let twitter_link = &selected_setting_opts.twitter_link;
-                                // let emoji = &setting_opts.emoji;
-                                let emoji = &selected_setting_opts.emoji;
-                                // let min_buy_amount = &setting_opts.min_buy_amount;
-                                let min_buy_amount = &selected_setting_opts.min_buy_amount;
-                                // let buy_step = &setting_opts.buy_step;
-                                let buy_step = &selected_setting_opts.buy_step;
-                                // let media_toggle = &setting_opts.media_toggle;
-                                let media_toggle = &selected_setting_opts.media_toggle;
-                                // let media_file_id = &setting_opts.media_file_id;
-                                let media_file_id = &selected_setting_opts.media_file_id;
-                                // let media_type = &setting_opts.media_type;
-                                let media_type = &selected_setting_opts.media_type;
-
-                                //get token overview
-                                let token_overview = get_token_overview(
-                                    request_client.clone(),
-                                    &debank_api_key,
-                                    &token_adr,
-                                )
-                                .await
-                                .unwrap();
-                                let token_price = token_overview.price;
-                                let token_price_output = num_floating_point(&token_price, 5);
-
-                                //make message
-                                let token_address = &token_transfer.items[0].token.address;
-                                let token_symbol = &token_transfer.items[0].token.symbol;
-                                let token_decimals = &token_transfer.items[0]
-                                    .token
-                                    .decimals
-                                    .parse()
-                                    .unwrap_or(0.0);
-                                let token_tx_decimal = &token_transfer.items[0]
-                                    .total
-                                    .decimals
-                                    .parse()
-                                    .unwrap_or(0.0);
-                                let token_tx_value =
-                                    &token_transfer.items[0].total.value.parse().unwrap_or(0.0)
-                                        / 10_f64.powi(*token_tx_decimal as i32);
-                                let token_total_supply = &token_transfer.items[0]
-                                    .token
-                                    .total_supply
-                                    .parse()
-                                    .unwrap_or(0.0);
-                                let total_supply =
-                                    *token_total_supply / 10_f64.powi(*token_decimals as i32);
-
-                                //get transaction info
-                                let tx_info =
-                                    get_tx_info(request_client.clone(), &flag_transaction_hash)
-                                        .await
-                                        .unwrap();
-                                let tx_value = token_tx_value
-                                    - tx_info.fee.value.parse().unwrap_or(0.0)
-                                        / 10_f64.powi(*token_decimals as i32);
-                                let tx_value_output = num_floating_point(&tx_value, 5);
-                                let tx_value_usd = controll_big_float(tx_value * token_price);
-                                let tx_total_usd = controll_big_float(token_tx_value * token_price);
-
-                                let mcap = controll_big_float(total_supply * token_price);
-
-                                let emoji_count = (tx_value / *buy_step as f64) as i32;
-                                let emoji_string = emoji.repeat((emoji_count + 1) as usize);
-
-                                if tx_value * token_price > *min_buy_amount {
-                                    let text = format!(
-                                        "{11}\n\n\
-                                        💲 Spent: ${1} (${7}) APE\n\
-                                        💰 Got: {5} ${2}\n\
-                                        ✅ Dex: <a href=\"https://ape.express/explore/{0}?\">Ape_Express</a> | \
-                                        🔖 <a href=\"https://t.me/Apechain_Trending_Bot\">Book Trending</a> - \
-                                        <a href=\"https://t.me/ApechainAds_Bot\">ADS</a>\n\
-                                        🏷️ Price: ${6}\n\
-                                        📊 Marketcap: ${4}\n\n\
-                                        <a href=\"https://apescan.io/tx/{3}\">TX</a> | \
-                                        <a href=\"https://dexscreener.com/apechain/{0}\">Chart</a> | \
-                                        <a href=\"{8}\">TG</a> | \
-                                        <a href=\"{9}\">X</a> | \
-                                        <a href=\"{10}\">Website</a>",
-                                        token_address, tx_value_usd,  token_symbol, flag_transaction_hash, mcap, tx_value_output, token_price_output, tx_total_usd, tg_link, twitter_link, website_link, emoji_string
-                                    );
-
-                                    if *media_toggle && media_file_id.clone().is_some() {
-                                        if media_type == "photo" {
-                                            bot.send_photo(
-                                                ChatId(group_chat_id.parse().expect("REASON")),
-                                                InputFile::file_id(media_file_id.clone().unwrap()),
-                                            )
-                                            .caption(text)
-                                            .parse_mode(teloxide::types::ParseMode::Html)
-                                            .await
-                                            .unwrap();
-                                        } else if media_type == "video" {
-                                            bot.send_video(
-                                                ChatId(group_chat_id.parse().expect("REASON")),
-                                                InputFile::file_id(media_file_id.clone().unwrap()),
-                                            )
-                                            .caption(text)
-                                            .parse_mode(teloxide::types::ParseMode::Html)
-                                            .await
-                                            .unwrap();
-                                        }
-                                    } else {
-                                        bot.send_message(
-                                            ChatId(group_chat_id.parse().expect("REASON")),
-                                            text,
-                                        )
-                                        .parse_mode(teloxide::types::ParseMode::Html)
-                                        .await
-                                        .unwrap();
-                                    }
-                                }
-                            }
-                        } else {
-                            bot.send_message(
-                                ChatId(group_chat_id.parse().expect("REASON")),
-                                "Not found any new transfer",
-                            )
-                            .await
-                            .unwrap();
-                        }
+    Some(setting_opts)
+}
+
+/// Takes the answer to the setting the dialogue waits for. A valid answer ends
+/// the dialogue, after an invalid one the admin can simply send it again.
+async fn answer_dialogue(
+    bot: Bot,
+    msg: Message,
+    dialogue: SettingsDialogue,
+    prompt: Prompt,
+    sessions: Arc<SessionStore>,
+    storage: Arc<dyn Storage>,
+    watchers: Arc<WatcherRegistry>,
+) -> ResponseResult<()> {
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+    let user_id = user.id.to_string();
+    let chat_id = msg.chat.id;
+
+    // Sessions only live in memory, after a restart the prompt tells which
+    // one to open again
+    if sessions.active_key(&user_id).await.is_none() {
+        resume_session(&sessions, user, &prompt).await;
+    }
+    let Some(key) = sessions.active_key(&user_id).await else {
+        return no_session(bot, chat_id, user_language(user)).await;
+    };
+    if key.group_chat_id != prompt.group_chat_id {
+        log::warn!("Dropping a prompt of user {} for another group", user_id);
+        let _ = dialogue.exit().await;
+        return Ok(());
+    }
+    let language = sessions
+        .get(&user_id)
+        .await
+        .map_or_else(|| user_language(user), |opts| Language::of(&opts));
+    if !is_group_admin(&bot, group_chat_id_of(&key.group_chat_id), user.id).await {
+        let _ = dialogue.exit().await;
+        return refuse_non_admin(bot, chat_id, &user_id, &sessions, language).await;
+    }
+    if !matches!(prompt.setting, Setting::TokenAddress { .. }) {
+        if let Err(token_address) = refresh_draft(&sessions, storage.as_ref(), &user_id).await {
+            let _ = dialogue.exit().await;
+            remove_prompt(&bot, chat_id, &prompt).await;
+            bot.send_message(
+                chat_id,
+                language.text_with("token-not-found", &[("token", &token_address)]),
+            )
+            .await?;
+            let tokens = group_tokens(storage.as_ref(), &key.group_chat_id).await;
+            return token_menu(bot, Screen::new_message(chat_id), &tokens, language).await;
+        }
+    }
+
+    if prompt.setting.is_media() {
+        let is_photo = matches!(prompt.setting, Setting::Photo | Setting::SellPhoto);
+        let file_id = if is_photo {
+            msg.photo()
+                .and_then(|sizes| sizes.first())
+                .map(|photo| photo.file.id.clone())
+        } else {
+            msg.video().map(|video| video.file.id.clone())
+        };
+        let Some(file_id) = file_id else {
+            let invalid_text = language.text(if is_photo {
+                "invalid-photo"
+            } else {
+                "invalid-video"
+            });
+            bot.send_message(chat_id, invalid_text).await?;
+            return Ok(());
+        };
+        let is_sell_media = matches!(prompt.setting, Setting::SellPhoto | Setting::SellVideo);
+        let Some(setting_opts) = sessions
+            .update(&user_id, |opts| {
+                if is_sell_media {
+                    opts.sell_media_file_id = Some(file_id);
+                } else {
+                    opts.media_file_id = Some(file_id);
+                }
+            })
+            .await
+        else {
+            return no_session(bot, chat_id, language).await;
+        };
+        let _ = dialogue.exit().await;
+        remove_prompt(&bot, chat_id, &prompt).await;
+        // Update the settings
+        let _ = storage.save_setting_opts(setting_opts.clone()).await;
+        apply_setting_opts(&watchers, setting_opts.clone()).await;
+
+        let head_text = language.text(if is_photo {
+            "saved-photo"
+        } else {
+            "saved-video"
+        });
+        return settings_panel(
+            bot,
+            Screen::new_message(chat_id),
+            head_text,
+            setting_opts,
+            Menu::Media,
+        )
+        .await;
+    }
+
+    let Some(text) = msg.text() else {
+        return Ok(());
+    };
+    // `Ok` with the text for a saved setting, `Err` for an invalid answer
+    let answer = match &prompt.setting {
+        Setting::TokenAddress { .. } => {
+            if !is_token_address(text) {
+                bot.send_message(chat_id, language.text("invalid-token-address"))
+                    .await?;
+                return Ok(());
+            }
+            // Saved the way the watchers key it, so a checksummed address is
+            // the same token
+            let token_address = text.to_lowercase();
+            let draft = sessions.get(&user_id).await.unwrap_or_default();
+            let mut existing_settings = match storage
+                .get_setting_opt(&key.group_chat_id, &token_address)
+                .await
+            {
+                Ok(existing_settings) => existing_settings,
+                Err(e) => {
+                    error!("Failed to load setting options: {}", e);
+                    return Ok(());
+                }
+            };
+            if existing_settings.chain_id != draft.chain_id {
+                // The token moves to the chain picked for it
+                watchers
+                    .stop(
+                        &key.group_chat_id,
+                        &existing_settings.chain_id,
+                        &token_address,
+                    )
+                    .await;
+                existing_settings.chain_id = draft.chain_id;
+            }
+            if existing_settings.language.is_empty() {
+                // A new token keeps the language of the draft
+                existing_settings.language = draft.language;
+            }
+            let Some(setting_opts) = sessions.replace(&user_id, existing_settings).await else {
+                return no_session(bot, chat_id, language).await;
+            };
+            let mut head_text = Language::of(&setting_opts).text("saved-token-address");
+            if !watchers
+                .has_dex_pairs(&setting_opts.chain_id, &setting_opts.token_address)
+                .await
+            {
+                // Without a pair every trade looks like a plain transfer
+                head_text = format!(
+                    "{}\n\n{}",
+                    Language::of(&setting_opts)
+                        .text_with("token-no-pairs", &[("token", &token_address)]),
+                    head_text
+                );
+            }
+
+            let _ = confirm_style_change(bot.clone(), &watchers, setting_opts).await;
+            Ok(head_text)
+        }
+        Setting::MinBuyAmount => match parse_usd_amount(text) {
+            Some(amount) => {
+                sessions
+                    .update(&user_id, |opts| opts.min_buy_amount = amount)
+                    .await;
+                Ok(language.text("saved-min-buy-amount"))
+            }
+            None => Err(language.text("invalid-min-buy-amount")),
+        },
+        Setting::BuyStep => match parse_buy_step(text) {
+            Some(step) => {
+                sessions.update(&user_id, |opts| opts.buy_step = step).await;
+                Ok(language.text("saved-buy-step"))
+            }
+            None => Err(language.text("invalid-buy-step")),
+        },
+        Setting::MinSellAmount => match parse_usd_amount(text) {
+            Some(amount) => {
+                sessions
+                    .update(&user_id, |opts| opts.min_sell_amount = amount)
+                    .await;
+                Ok(language.text("saved-min-sell-amount"))
+            }
+            None => Err(language.text("invalid-min-sell-amount")),
+        },
+        Setting::SellEmoji => {
+            if is_emoji(text) {
+                sessions
+                    .update(&user_id, |opts| opts.sell_emoji = text.to_string())
+                    .await;
+                Ok(language.text("saved-sell-emoji"))
+            } else {
+                Err(language.text("invalid-sell-emoji"))
+            }
+        }
+        Setting::BuyTemplate => {
+            let is_reset_word = |language: Language| {
+                text.trim()
+                    .eq_ignore_ascii_case(&language.text("template-reset-word"))
+            };
+            if Language::ALL.into_iter().any(is_reset_word) {
+                sessions
+                    .update(&user_id, |opts| opts.buy_template = None)
+                    .await;
+                Ok(language.text("reset-buy-template"))
+            } else {
+                match Template::parse(text) {
+                    Ok(_) => {
+                        sessions
+                            .update(&user_id, |opts| opts.buy_template = Some(text.to_string()))
+                            .await;
+                        Ok(language.text("saved-buy-template"))
                     }
                     Err(e) => {
-                        error!("Error fetching token overview: {}", e);
-                        continue;
+                        bot.send_message(
+                            chat_id,
+                            language.text_with(
+                                "template-invalid",
+                                &[("error", &e.localized(language))],
+                            ),
+                        )
+                        .await?;
+                        Err(language.text("invalid-buy-template"))
                     }
-                };
+                }
+            }
+        }
+        Setting::Emoji => {
+            if is_emoji(text) {
+                sessions
+                    .update(&user_id, |opts| opts.emoji = text.to_string())
+                    .await;
+                Ok(language.text("saved-emoji"))
             } else {
-                break;
+                Err(language.text("invalid-emoji"))
             }
         }
-    });
-    Ok(())
+        Setting::TgLink => {
+            if is_tg_link(text) {
+                sessions
+                    .update(&user_id, |opts| opts.tg_link = text.to_string())
+                    .await;
+                Ok(language.text("saved-tg-link"))
+            } else {
+                Err(language.text("invalid-tg-link"))
+            }
+        }
+        Setting::WebsiteLink => {
+            if is_website_link(text) {
+                sessions
+                    .update(&user_id, |opts| opts.website_link = text.to_string())
+                    .await;
+                Ok(language.text("saved-website-link"))
+            } else {
+                Err(language.text("invalid-website-link"))
+            }
+        }
+        Setting::TwitterLink => {
+            if is_twitter_link(text) {
+                sessions
+                    .update(&user_id, |opts| opts.twitter_link = text.to_string())
+                    .await;
+                Ok(language.text("saved-twitter-link"))
+            } else {
+                Err(language.text("invalid-twitter-link"))
+            }
+        }
+        Setting::Photo | Setting::Video | Setting::SellPhoto | Setting::SellVideo => {
+            return Ok(());
+        }
+    };
+    let head_text = match answer {
+        Ok(head_text) => head_text,
+        // The prompt stays, the next message is another try
+        Err(invalid_text) => {
+            bot.send_message(chat_id, invalid_text).await?;
+            return Ok(());
+        }
+    };
+    let _ = dialogue.exit().await;
+    remove_prompt(&bot, chat_id, &prompt).await;
+
+    let Some(setting_opts) = sessions.get(&user_id).await else {
+        return no_session(bot, chat_id, language).await;
+    };
+    let _ = storage.save_setting_opts(setting_opts.clone()).await;
+    if !matches!(prompt.setting, Setting::TokenAddress { .. }) {
+        apply_setting_opts(&watchers, setting_opts.clone()).await;
+    }
+
+    settings_panel(
+        bot,
+        Screen::new_message(chat_id),
+        head_text,
+        setting_opts,
+        Menu::of(&prompt.setting),
+    )
+    .await
+}
+
+/// Deletes an answered prompt, the settings follow in a new message below the
+/// answer.
+async fn remove_prompt(bot: &Bot, chat_id: ChatId, prompt: &Prompt) {
+    if let Some(message_id) = prompt.message_id {
+        if let Err(e) = bot.delete_message(chat_id, MessageId(message_id)).await {
+            log::info!("Could not delete prompt {}: {}", message_id, e);
+        }
+    }
+}
+
+/// Opens the session a prompt was sent in again, e.g. after a restart. The
+/// settings of its token are loaded by `refresh_draft`.
+async fn resume_session(sessions: &SessionStore, user: &User, prompt: &Prompt) {
+    open_session(sessions, user, group_chat_id_of(&prompt.group_chat_id)).await;
+    sessions
+        .update(&user.id.to_string(), |opts| match &prompt.setting {
+            Setting::TokenAddress { chain_id } => opts.chain_id = chain_id.clone(),
+            _ => opts.token_address = prompt.token_address.clone(),
+        })
+        .await;
 }
 
-async fn delete_and_back_to_new_token(
+/// The settings of the draft's token, one menu at a time.
+async fn settings_panel(
     bot: Bot,
-    chat_id: ChatId,
-    setting_opts_arc: Arc<RwLock<SettingOpts>>,
+    screen: Screen,
+    head_text: String,
+    setting_opts: SettingOpts,
+    menu: Menu,
 ) -> ResponseResult<()> {
-    let pool = get_conn_pool();
-    let is_deleted = delete_setting_opt_from_db(
-        &pool,
-        &setting_opts_arc.read().await.token_address,
-        setting_opts_arc.read().await.user_id.clone(),
-    )
-    .await
-    .unwrap();
-    if is_deleted {
-        bot.send_message(
-            chat_id,
-            format!(
-                "The token {} is deleted. Please return to group chat.",
-                setting_opts_arc.read().await.token_address
+    let language = Language::of(&setting_opts);
+    let label = |id: &str, value: &str| language.text_with(id, &[("value", value)]);
+    let on_off = |enabled: bool| language.text(if enabled { "value-on" } else { "value-off" });
+    let button =
+        |text: String, callback: &str| vec![InlineKeyboardButton::callback(text, callback)];
+    let mut rows = match menu {
+        Menu::Main => vec![
+            button(
+                language.text("button-menu-thresholds"),
+                Menu::Thresholds.callback(),
             ),
-        )
-        .await?;
-        *setting_opts_arc.write().await = SettingOpts::default();
-    } else {
-        bot.send_message(
-            chat_id,
-            format!(
-                "The token {} is not found.",
-                setting_opts_arc.read().await.token_address
+            button(language.text("button-menu-media"), Menu::Media.callback()),
+            button(language.text("button-menu-links"), Menu::Links.callback()),
+            button(label("button-emoji", &setting_opts.emoji), "emoji"),
+            button(
+                label(
+                    "button-buy-template",
+                    &language.text(if setting_opts.buy_template.is_some() {
+                        "template-custom"
+                    } else {
+                        "template-default"
+                    }),
+                ),
+                "buy_template",
             ),
-        )
-        .await?;
+            button(
+                label("button-sell-alerts", &on_off(setting_opts.sell_alerts)),
+                "sell_alerts",
+            ),
+            button(
+                label("button-sell-emoji", &setting_opts.sell_emoji),
+                "sell_emoji",
+            ),
+            button(label("button-language", &language.name()), "language"),
+            button(language.text("button-tokens"), "tokens"),
+            button(language.text("button-delete-token"), "delete_token"),
+            // vec![
+            //     // InlineKeyboardButton::callback("Confirm", "confirm"),
+            //     InlineKeyboardButton::url(
+            //         "Go back to group",
+            //         format!("https://t.me/c/{}", group_chat_id).parse().unwrap()
+            //     )
+            // ]
+        ],
+        Menu::Thresholds => vec![
+            button(
+                label("button-min-buy", &setting_opts.min_buy_amount.to_string()),
+                "min_buy_amount",
+            ),
+            button(
+                label("button-buy-step", &setting_opts.buy_step.to_string()),
+                "buy_step",
+            ),
+            button(
+                label("button-min-sell", &setting_opts.min_sell_amount.to_string()),
+                "min_sell_amount",
+            ),
+        ],
+        Menu::Media => vec![
+            button(
+                label("button-media-toggle", &on_off(setting_opts.media_toggle)),
+                "media_toggle",
+            ),
+            button(
+                label(
+                    "button-add-media",
+                    setting_opts.media_file_id.as_deref().unwrap_or_default(),
+                ),
+                "add_media",
+            ),
+            button(
+                label(
+                    "button-add-sell-media",
+                    setting_opts
+                        .sell_media_file_id
+                        .as_deref()
+                        .unwrap_or_default(),
+                ),
+                "add_sell_media",
+            ),
+        ],
+        Menu::Links => vec![
+            button(label("button-tg-link", &setting_opts.tg_link), "tg_link"),
+            button(
+                label("button-twitter-link", &setting_opts.twitter_link),
+                "twitter_link",
+            ),
+            button(
+                label("button-website-link", &setting_opts.website_link),
+                "website_link",
+            ),
+        ],
+    };
+    if menu != Menu::Main {
+        rows.push(button(language.text("button-back"), Menu::Main.callback()));
     }
-    // println!("delete_and_back_to_new_token:  {:?}", setting_opts_arc.read().await);
+
+    show(&bot, screen, head_text, InlineKeyboardMarkup::new(rows)).await?;
 
     Ok(())
 }
 
-async fn get_token_transfers(
-    client: Client,
-    token_address: &str,
-) -> Result<TokenTransfer, Box<dyn std::error::Error + Send + Sync>> {
-    let url = format!(
-        "https://apechain.calderaexplorer.xyz/api/v2/tokens/{}/transfers",
-        token_address
+async fn confirm_style_change(
+    bot: Bot,
+    watchers: &WatcherRegistry,
+    setting_opts: SettingOpts,
+) -> ResponseResult<()> {
+    let group_chat_id = setting_opts.group_chat_id.clone();
+    let chain_name = find_chain(&setting_opts.chain_id).map_or("", |chain| chain.name);
+    let text = Language::of(&setting_opts).text_with(
+        "tracking-started",
+        &[
+            ("token", &setting_opts.token_address),
+            ("chain", chain_name),
+        ],
     );
-
-    let response = client.get(&url).send().await?;
-
-    let text = response.text().await?;
-
-    match serde_json::from_str::<TokenTransfer>(&text) {
-        Ok(transfer) => Ok(transfer),
-        Err(e) => {
-            error!("Deserialization error: {}", e);
-            Err(Box::new(e))
+    match watchers.start(setting_opts).await {
+        Ok(true) => {
+            // `start` only accepts numeric group ids
+            bot.send_message(group_chat_id_of(&group_chat_id), text)
+                .await?;
         }
+        Ok(false) => {}
+        Err(e) => error!("Failed to start buy watcher for {}: {}", group_chat_id, e),
     }
+    Ok(())
 }
 
-async fn get_tx_info(
-    client: Client,
-    tx_hash: &str,
-) -> Result<TxInfo, Box<dyn std::error::Error + Send + Sync>> {
-    let url = format!(
-        "https://apechain.calderaexplorer.xyz/api/v2/transactions/{}",
-        tx_hash
-    );
-    let response = client.get(&url).send().await?;
-    let text = response.text().await?;
-    match serde_json::from_str::<TxInfo>(&text) {
-        Ok(tx_info) => Ok(tx_info),
-        Err(e) => {
-            error!("Deserialization error: {}", e);
-            Err(Box::new(e))
-        }
+/// Restarts the watcher of a tracked token so it picks up changed settings.
+async fn apply_setting_opts(watchers: &WatcherRegistry, setting_opts: SettingOpts) {
+    if setting_opts.token_address.is_empty() {
+        return;
+    }
+    let group_chat_id = setting_opts.group_chat_id.clone();
+    if let Err(e) = watchers.restart(setting_opts).await {
+        error!("Failed to restart buy watcher for {}: {}", group_chat_id, e);
     }
 }
 
-async fn get_token_overview(
-    client: Client,
-    api_key: &str,
-    token_address: &str,
-) -> Result<TokenOverview, Box<dyn std::error::Error + Send + Sync>> {
-    let url = format!(
-        "https://pro-openapi.debank.com/v1/token?chain_id=ape&id={}",
-        token_address
-    );
-    let response = client.get(&url).header("Accesskey", api_key).send().await?;
-    let text = response.text().await?;
-    match serde_json::from_str::<TokenOverview>(&text) {
-        Ok(token_overview) => Ok(token_overview),
+async fn resume_buy_watchers(watchers: &WatcherRegistry, storage: &dyn Storage) {
+    let rows = match storage.get_all_setting_opts().await {
+        Ok(rows) => rows,
         Err(e) => {
-            error!("Deserialization error: {}", e);
-            Err(Box::new(e))
+            error!(
+                "Failed to load setting options for resuming watchers: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    let mut resumed = 0;
+    for row in rows {
+        match row {
+            Ok(setting_opts) => {
+                let group_chat_id = setting_opts.group_chat_id.clone();
+                let token_address = setting_opts.token_address.clone();
+                match watchers.start(setting_opts).await {
+                    Ok(true) => resumed += 1,
+                    Ok(false) => {}
+                    Err(e) => error!(
+                        "Failed to resume buy watcher for group {} and token {}: {}",
+                        group_chat_id, token_address, e
+                    ),
+                }
+            }
+            Err(e) => error!("Skipping unreadable setting_opts row: {}", e),
         }
     }
+    log::info!("Resumed {} buy watchers", resumed);
 }
 
-fn num_floating_point(num: &f64, length: i32) -> f64 {
-    ((num * 10_f64.powi(length as i32)).round()) / 10_f64.powi(length as i32)
+/// The group's tracked tokens. A failed lookup is logged and lists none.
+async fn group_tokens(storage: &dyn Storage, group_chat_id: &str) -> Vec<SettingOpts> {
+    match storage.get_group_setting_opts(group_chat_id).await {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            error!(
+                "Failed to load the tokens of group {}: {}",
+                group_chat_id, e
+            );
+            Vec::new()
+        }
+    }
 }
 
-fn controll_big_float(num: f64) -> String {
-    if num > 1_000_000.0 {
-        format!("{:.1}M", num / 1_000_000.0)
-    } else if num > 1_000.0 {
-        format!("{:.2}K", num / 1000.0)
-    } else {
-        format!("{:.3}", num)
+/// The group's tokens, one per line with their chain.
+fn tokens_text(tokens: &[SettingOpts], language: Language) -> String {
+    if tokens.is_empty() {
+        return language.text("tokens-none");
     }
+    let lines = tokens
+        .iter()
+        .map(|opts| {
+            let chain_name = find_chain(&opts.chain_id).map_or("", |chain| chain.name);
+            format!("• {} ({})", opts.token_address, chain_name)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    language.text_with("tokens-list", &[("tokens", &lines)])
 }
 
-fn init_database(pool: &Pool) -> Result<(), Box<dyn std::error::Error>> {
-    let mut conn = pool.get_conn()?;
-
-    // Create user_info table first
-    conn.query_drop(
-        r"
-        CREATE TABLE IF NOT EXISTS user_info (
-            id BIGINT AUTO_INCREMENT PRIMARY KEY,
-            user_id BIGINT NOT NULL UNIQUE,
-            username VARCHAR(255),
-            first_name VARCHAR(255),
-            last_name VARCHAR(255),
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-        ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
-    ",
-    )?;
-
-    // Then create setting_opts table with foreign key
-    conn.query_drop(
-        r"
-        CREATE TABLE IF NOT EXISTS setting_opts (
-            id VARCHAR(255) PRIMARY KEY,
-            user_id VARCHAR(255) NOT NULL,
-            group_chat_id VARCHAR(255) NOT NULL,
-            token_address VARCHAR(42) NOT NULL,
-            min_buy_amount DOUBLE NOT NULL,
-            buy_step INT NOT NULL,
-            emoji VARCHAR(10) NOT NULL,
-            media_toggle BOOLEAN NOT NULL,
-            media_file_id VARCHAR(255),
-            media_type VARCHAR(10),
-            tg_link VARCHAR(255),
-            website_link VARCHAR(255),
-            twitter_link VARCHAR(255),
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            UNIQUE KEY unique_id (id)
-        ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
-    ",
-    )?;
+/// Lists the group's tokens with buttons to edit or remove each of them and
+/// to add another one.
+async fn token_menu(
+    bot: Bot,
+    screen: Screen,
+    tokens: &[SettingOpts],
+    language: Language,
+) -> ResponseResult<()> {
+    let mut rows = tokens
+        .iter()
+        .map(|opts| {
+            let chain_name = find_chain(&opts.chain_id).map_or("", |chain| chain.name);
+            vec![
+                InlineKeyboardButton::callback(
+                    language.text_with(
+                        "button-edit-token",
+                        &[
+                            ("token", &short_address(&opts.token_address)),
+                            ("chain", chain_name),
+                        ],
+                    ),
+                    format!("edit_token_{}", opts.token_address),
+                ),
+                InlineKeyboardButton::callback(
+                    language.text("button-remove-token"),
+                    format!("remove_token_{}", opts.token_address),
+                ),
+            ]
+        })
+        .collect::<Vec<_>>();
+    rows.push(vec![InlineKeyboardButton::callback(
+        language.text("button-add-token"),
+        "add_token",
+    )]);
+
+    show(
+        &bot,
+        screen,
+        tokens_text(tokens, language),
+        InlineKeyboardMarkup::new(rows),
+    )
+    .await?;
 
     Ok(())
 }
 
-async fn save_user_info(pool: &Pool, user: UserInfo) -> Result<(), Box<dyn std::error::Error>> {
-    let mut conn = pool.get_conn()?;
+/// Starts over with an empty draft for another token of the group.
+async fn add_token(
+    bot: Bot,
+    screen: Screen,
+    user_id: &str,
+    sessions: &SessionStore,
+    language: Language,
+) -> ResponseResult<()> {
+    if sessions.reset(user_id).await.is_none() {
+        return no_session(bot, screen.chat_id, language).await;
+    }
+    start(bot, screen, language).await
+}
 
-    let params = params! {
-        "user_id" => user.user_id,
-        "username" => &user.username,
-        "first_name" => &user.first_name,
-        "last_name" => &user.last_name,
+/// Loads a token of the group into the draft and shows its settings. Returns
+/// the toast for a token that is gone.
+async fn edit_token(
+    bot: Bot,
+    screen: Screen,
+    user_id: &str,
+    sessions: &SessionStore,
+    storage: &dyn Storage,
+    token_address: &str,
+) -> ResponseResult<Option<String>> {
+    let Some(draft) = sessions.get(user_id).await else {
+        return Ok(Some(Language::default().text("no-session")));
     };
-    // println!("Params: {:?}", params);
-    conn.exec_drop(
-        r"INSERT INTO user_info 
-          (user_id, username, first_name, last_name)
-          VALUES (:user_id, :username, :first_name, :last_name)
-          ON DUPLICATE KEY UPDATE
-          username = :username,
-          first_name = :first_name,
-          last_name = :last_name",
-        params,
-    )?;
+    let language = Language::of(&draft);
+    let tokens = group_tokens(storage, &draft.group_chat_id).await;
+    let Some(setting_opts) = tokens
+        .iter()
+        .find(|opts| opts.token_address == token_address)
+        .cloned()
+    else {
+        // Another admin removed it since the list was shown
+        token_menu(bot, screen, &tokens, language).await?;
+        return Ok(Some(
+            language.text_with("token-not-found", &[("token", token_address)]),
+        ));
+    };
+    let Some(setting_opts) = sessions.replace(user_id, setting_opts).await else {
+        return Ok(Some(language.text("no-session")));
+    };
+    let language = Language::of(&setting_opts);
+
+    settings_panel(
+        bot,
+        screen,
+        language.text_with("editing-token", &[("token", token_address)]),
+        setting_opts,
+        Menu::Main,
+    )
+    .await?;
+    Ok(None)
+}
+
+/// Asks before a token stops being tracked. `back` is the callback of the
+/// menu the removal started from.
+async fn confirm_removal(
+    bot: Bot,
+    screen: Screen,
+    token_address: &str,
+    back: &str,
+    language: Language,
+) -> ResponseResult<()> {
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback(
+            language.text("button-confirm-remove"),
+            format!("confirm_remove_{}", token_address),
+        ),
+        InlineKeyboardButton::callback(language.text("button-cancel"), back),
+    ]]);
+
+    show(
+        &bot,
+        screen,
+        language.text_with("remove-token-confirm", &[("token", token_address)]),
+        keyboard,
+    )
+    .await?;
 
     Ok(())
 }
 
-async fn save_setting_opts_db(
-    pool: &Pool,
-    opt: SettingOpts,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut conn = pool.get_conn()?;
-
-    let params = params! {
-        "id" => format!("{}/{}/{}", &opt.user_id, &opt.group_chat_id, &opt.token_address),
-        "user_id" => &opt.user_id,
-        "group_chat_id" => opt.group_chat_id,
-        "token_address" => &opt.token_address,
-        "min_buy_amount" => opt.min_buy_amount,
-        "buy_step" => opt.buy_step,
-        "emoji" => &opt.emoji,
-        "media_toggle" => opt.media_toggle,
-        "media_file_id" => &opt.media_file_id,
-        "media_type" => &opt.media_type,
-        "tg_link" => &opt.tg_link,
-        "website_link" => &opt.website_link,
-        "twitter_link" => &opt.twitter_link
+/// Stops tracking a token of the group, for every admin, and lists the rest.
+/// Returns the toast telling how it went.
+async fn remove_token(
+    bot: Bot,
+    screen: Screen,
+    user_id: &str,
+    sessions: &SessionStore,
+    storage: &dyn Storage,
+    watchers: &WatcherRegistry,
+    token_address: &str,
+) -> ResponseResult<Option<String>> {
+    let Some(draft) = sessions.get(user_id).await else {
+        return Ok(Some(Language::default().text("no-session")));
     };
-
-    match conn.exec_drop(
-        r"INSERT INTO setting_opts 
-          (id, user_id, group_chat_id, token_address, min_buy_amount, buy_step, emoji, 
-           media_toggle, media_file_id, media_type, tg_link, website_link, twitter_link)
-          VALUES 
-          (:id, :user_id, :group_chat_id, :token_address, :min_buy_amount, :buy_step, :emoji,
-           :media_toggle, :media_file_id, :media_type, :tg_link, :website_link, :twitter_link)
-          ON DUPLICATE KEY UPDATE
-          user_id = :user_id,
-          group_chat_id = :group_chat_id,
-          token_address = :token_address,
-          min_buy_amount = :min_buy_amount,
-          buy_step = :buy_step,
-          emoji = :emoji,
-          media_toggle = :media_toggle, 
-          media_file_id = :media_file_id,
-          media_type = :media_type,
-          tg_link = :tg_link,
-          website_link = :website_link,
-          twitter_link = :twitter_link",
-        params,
-    ) {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            eprintln!("Database error: {}", e);
-            Err(Box::new(e))
+    let language = Language::of(&draft);
+    let tokens = group_tokens(storage, &draft.group_chat_id).await;
+    // The watcher to stop runs on the chain of the saved token
+    let removed = tokens
+        .iter()
+        .find(|opts| opts.token_address == token_address);
+    let is_deleted = match removed {
+        Some(_) => match storage
+            .delete_setting_opt(&draft.group_chat_id, token_address)
+            .await
+        {
+            Ok(is_deleted) => is_deleted,
+            Err(e) => {
+                error!("Failed to delete setting options: {}", e);
+                false
+            }
+        },
+        None => false,
+    };
+    let toast = match removed {
+        Some(removed) if is_deleted => {
+            watchers
+                .stop(&removed.group_chat_id, &removed.chain_id, token_address)
+                .await;
+            if draft.token_address == token_address {
+                sessions.reset(user_id).await;
+            }
+            language.text_with("token-deleted", &[("token", token_address)])
         }
-    }?;
+        _ => language.text_with("token-not-found", &[("token", token_address)]),
+    };
 
-    Ok(())
+    let tokens = group_tokens(storage, &draft.group_chat_id).await;
+    token_menu(bot, screen, &tokens, language).await?;
+    Ok(Some(toast))
 }
 
-async fn get_setting_opt(
-    pool: &Pool,
-    user_id: String,
-    group_id: String,
-    token_adr: String,
-) -> Result<SettingOpts, Box<dyn std::error::Error>> {
-    let mut conn = pool.get_conn()?;
-    let result = conn.exec_first(
-        r"SELECT 
-            CAST(user_id AS CHAR) as user_id,
-            CAST(group_chat_id AS CHAR) as group_chat_id,
-            CAST(token_address AS CHAR) as token_address,
-            min_buy_amount,
-            buy_step,
-            CAST(emoji AS CHAR) as emoji,
-            media_toggle,
-            NULLIF(CAST(media_file_id AS CHAR), '') as media_file_id,
-            CAST(media_type AS CHAR) as media_type,
-            CAST(tg_link AS CHAR) as tg_link,
-            CAST(website_link AS CHAR) as website_link,
-            CAST(twitter_link AS CHAR) as twitter_link
-          FROM setting_opts 
-          WHERE token_address = ? AND user_id = ? AND group_chat_id = ?
-          LIMIT 1",
-        (token_adr.clone(), user_id.clone(), group_id.clone()),
-    )?;
-    // println!("result@@@: {:?}", result);
-    if let Some((
-        user_id,
-        group_chat_id,
-        token_address,
-        min_buy_amount,
-        buy_step,
-        emoji,
-        media_toggle,
-        media_file_id,
-        media_type,
-        tg_link,
-        website_link,
-        twitter_link,
-    )) = result
+/// Reloads the token of the draft, which another admin of the group may have
+/// changed since it was loaded. `Err` holds the address of a token another
+/// admin removed, it is dropped from the draft so nothing saves it again.
+async fn refresh_draft(
+    sessions: &SessionStore,
+    storage: &dyn Storage,
+    user_id: &str,
+) -> Result<Option<SettingOpts>, String> {
+    let Some(draft) = sessions.get(user_id).await else {
+        return Ok(None);
+    };
+    if draft.token_address.is_empty() {
+        return Ok(Some(draft));
+    }
+    match storage
+        .find_setting_opt(&draft.group_chat_id, &draft.token_address)
+        .await
     {
-        Ok(SettingOpts {
-            user_id,
-            group_chat_id,
-            token_address,
-            min_buy_amount,
-            buy_step,
-            emoji,
-            media_toggle,
-            media_file_id,
-            media_type,
-            tg_link,
-            website_link,
-            twitter_link,
-        })
-    } else {
-        Ok(SettingOpts {
-            user_id: user_id.clone(),
-            group_chat_id: group_id.clone(),
-            token_address: token_adr.to_string(),
-            min_buy_amount: 0.0,
-            buy_step: 30,
-            emoji: "💎".to_string(),
-            media_toggle: true,
-            media_type: String::new(),
-            media_file_id: Some(String::new()),
-            tg_link: String::new(),
-            twitter_link: String::new(),
-            website_link: String::new(),
-        })
+        Ok(Some(saved)) => Ok(sessions.replace(user_id, saved).await),
+        Ok(None) => {
+            sessions.reset(user_id).await;
+            Err(draft.token_address)
+        }
+        Err(e) => {
+            error!("Failed to load setting options: {}", e);
+            Ok(Some(draft))
+        }
     }
 }
 
-async fn delete_setting_opt_from_db(
-    pool: &Pool,
+async fn get_dex_pairs(
+    client: Client,
     token_address: &str,
-    user_id: String,
-) -> Result<bool, Box<dyn std::error::Error>> {
-    let mut conn = pool.get_conn()?;
-
-    let _result = conn.exec_drop(
-        r"DELETE FROM setting_opts 
-          WHERE token_address = ? AND user_id = ?",
-        (token_address, user_id),
-    )?;
-
-    // Check if any row was affected
-    let affected_rows = conn.affected_rows();
-    Ok(affected_rows > 0)
+) -> Result<DexPairs, Box<dyn std::error::Error + Send + Sync>> {
+    let url = format!(
+        "https://api.dexscreener.com/latest/dex/tokens/{}",
+        token_address
+    );
+    let response = client.get(&url).send().await?;
+    let text = response.text().await?;
+    match serde_json::from_str::<DexPairs>(&text) {
+        Ok(dex_pairs) => Ok(dex_pairs),
+        Err(e) => {
+            error!("Deserialization error: {}", e);
+            Err(Box::new(e))
+        }
+    }
 }
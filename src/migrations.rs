@@ -0,0 +1,268 @@
+use mysql::prelude::*;
+use mysql::*;
+
+use crate::storage::StorageResult;
+
+/// A numbered schema change. The SQL lives in `migrations/` and is embedded
+/// in the binary, so a deploy never needs the files next to it.
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// Every migration, oldest first. Append new ones here; never edit or
+/// renumber one that has been released.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial",
+        sql: include_str!("../migrations/0001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "sell_alerts",
+        sql: include_str!("../migrations/0002_sell_alerts.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "chain_id",
+        sql: include_str!("../migrations/0003_chain_id.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "buy_template",
+        sql: include_str!("../migrations/0004_buy_template.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "language",
+        sql: include_str!("../migrations/0005_language.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "dialogue_state",
+        sql: include_str!("../migrations/0006_dialogue_state.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "group_tokens",
+        sql: include_str!("../migrations/0007_group_tokens.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "lowercase_tokens",
+        sql: include_str!("../migrations/0008_lowercase_tokens.sql"),
+    },
+];
+
+// Databases created before migrations existed may already contain a table,
+// column or index the migration is about to add.
+const ER_TABLE_EXISTS: u16 = 1050;
+const ER_DUP_FIELDNAME: u16 = 1060;
+const ER_DUP_KEYNAME: u16 = 1061;
+
+/// Migrations that are applied and still to apply.
+pub struct MigrationStatus {
+    pub current_version: u32,
+    pub applied: Vec<(u32, String, String)>,
+    pub pending: Vec<&'static Migration>,
+}
+
+impl std::fmt::Display for MigrationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Schema version: {}", self.current_version)?;
+        for (version, name, applied_at) in &self.applied {
+            writeln!(f, "  [applied] {:04}_{} ({})", version, name, applied_at)?;
+        }
+        for migration in &self.pending {
+            writeln!(f, "  [pending] {:04}_{}", migration.version, migration.name)?;
+        }
+        Ok(())
+    }
+}
+
+impl MigrationStatus {
+    /// The statements applying the pending migrations would run, as a script.
+    pub fn pending_sql(&self) -> String {
+        self.pending
+            .iter()
+            .map(|migration| {
+                let statements = statements(migration.sql)
+                    .into_iter()
+                    .map(|statement| format!("{};\n", statement))
+                    .collect::<String>();
+                format!(
+                    "-- {:04}_{}\n{}",
+                    migration.version, migration.name, statements
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Reads which migrations are applied without changing the database. A
+/// database that never ran a migration has no version table yet.
+pub fn status(conn: &mut PooledConn) -> StorageResult<MigrationStatus> {
+    let has_version_table: Option<u32> = conn.query_first(
+        r"SELECT 1
+          FROM information_schema.tables
+          WHERE table_schema = DATABASE() AND table_name = 'schema_version'",
+    )?;
+    let applied: Vec<(u32, String, String)> = match has_version_table {
+        Some(_) => conn.query(
+            r"SELECT version, name, CAST(applied_at AS CHAR)
+              FROM schema_version
+              ORDER BY version",
+        )?,
+        None => Vec::new(),
+    };
+    let current_version = applied.iter().map(|(v, _, _)| *v).max().unwrap_or(0);
+    let pending = MIGRATIONS
+        .iter()
+        .filter(|m| m.version > current_version)
+        .collect();
+
+    Ok(MigrationStatus {
+        current_version,
+        applied,
+        pending,
+    })
+}
+
+/// Applies every pending migration in order and returns the versions that
+/// were applied. See `MigrationStatus::pending_sql` for a dry run.
+pub fn run(conn: &mut PooledConn) -> StorageResult<Vec<u32>> {
+    ensure_version_table(conn)?;
+    let pending = status(conn)?.pending;
+    let mut applied = Vec::new();
+
+    for migration in pending {
+        log::info!(
+            "Applying migration {:04}_{}",
+            migration.version,
+            migration.name
+        );
+        apply(conn, migration)?;
+        applied.push(migration.version);
+    }
+
+    Ok(applied)
+}
+
+fn apply(conn: &mut PooledConn, migration: &Migration) -> StorageResult<()> {
+    for statement in statements(migration.sql) {
+        match conn.query_drop(statement) {
+            Ok(()) => {}
+            Err(Error::MySqlError(e))
+                if [ER_TABLE_EXISTS, ER_DUP_FIELDNAME, ER_DUP_KEYNAME].contains(&e.code) =>
+            {
+                log::warn!(
+                    "Migration {:04}_{}: {} (already applied, skipping)",
+                    migration.version,
+                    migration.name,
+                    e.message
+                );
+            }
+            Err(e) => {
+                return Err(format!(
+                    "migration {:04}_{} failed: {}",
+                    migration.version, migration.name, e
+                )
+                .into())
+            }
+        }
+    }
+
+    conn.exec_drop(
+        "INSERT INTO schema_version (version, name) VALUES (?, ?)",
+        (migration.version, migration.name),
+    )?;
+    Ok(())
+}
+
+fn ensure_version_table(conn: &mut PooledConn) -> StorageResult<()> {
+    conn.query_drop(
+        r"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INT UNSIGNED PRIMARY KEY,
+            name VARCHAR(255) NOT NULL,
+            applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
+    ",
+    )?;
+    Ok(())
+}
+
+/// Splits a migration file into statements. Statements end with `;` at the
+/// end of a line, so semicolons inside string literals are left alone.
+fn statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+
+    for line in sql.lines() {
+        if line.trim_start().starts_with("--") {
+            continue;
+        }
+        current.push_str(line);
+        current.push('\n');
+        if line.trim_end().ends_with(';') {
+            let statement = current.trim().trim_end_matches(';').trim().to_string();
+            if !statement.is_empty() {
+                statements.push(statement);
+            }
+            current.clear();
+        }
+    }
+    let rest = current.trim();
+    if !rest.is_empty() {
+        statements.push(rest.to_string());
+    }
+
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_strictly_increasing() {
+        for pair in MIGRATIONS.windows(2) {
+            assert!(pair[0].version < pair[1].version);
+        }
+    }
+
+    #[test]
+    fn every_migration_has_statements() {
+        for migration in MIGRATIONS {
+            let statements = statements(migration.sql);
+            assert!(!statements.is_empty(), "{} is empty", migration.name);
+            assert!(statements.iter().all(|s| !s.ends_with(';')));
+        }
+        assert_eq!(statements(MIGRATIONS[1].sql).len(), 5);
+    }
+
+    #[test]
+    fn dry_runs_print_every_pending_statement() {
+        let status = MigrationStatus {
+            current_version: 6,
+            applied: Vec::new(),
+            pending: MIGRATIONS.iter().filter(|m| m.version > 6).collect(),
+        };
+        let sql = status.pending_sql();
+
+        assert!(
+            sql.starts_with("-- 0007_group_tokens\nDELETE older FROM"),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.contains("CREATE INDEX idx_setting_opts_group ON setting_opts (group_chat_id);\n")
+        );
+        assert!(sql.contains("\n\n-- 0008_lowercase_tokens\nUPDATE setting_opts"));
+        // Comments in the files are left out
+        assert!(!sql.contains("-- Tokens belong"));
+    }
+}
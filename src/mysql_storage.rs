@@ -0,0 +1,321 @@
+use async_trait::async_trait;
+use mysql::prelude::*;
+use mysql::*;
+
+use crate::config::DbConfig;
+use crate::migrations::{self, MigrationStatus};
+use crate::setting_opts::SettingOpts;
+use crate::storage::{setting_opts_id, Storage, StorageResult};
+use crate::user_info::UserInfo;
+
+/// `Storage` backed by the bot's MySQL database.
+pub struct MysqlStorage {
+    pool: Pool,
+}
+
+impl MysqlStorage {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates the connection pool and makes sure the database is reachable.
+    pub fn connect(config: &DbConfig) -> StorageResult<Self> {
+        let constraints = PoolConstraints::new(config.pool_min, config.pool_max)
+            .ok_or("invalid pool size constraints")?;
+        let opts = OptsBuilder::from_opts(Opts::from_url(&config.url)?)
+            .pool_opts(PoolOpts::default().with_constraints(constraints))
+            .tcp_connect_timeout(Some(config.connect_timeout))
+            .read_timeout(Some(config.read_timeout))
+            .write_timeout(Some(config.write_timeout));
+        let pool = Pool::new(opts)?;
+        pool.get_conn()?;
+        Ok(Self::new(pool))
+    }
+
+    pub fn migration_status(&self) -> StorageResult<MigrationStatus> {
+        migrations::status(&mut self.pool.get_conn()?)
+    }
+
+    /// Runs `query` on a pooled connection on the blocking threads. The
+    /// `mysql` driver blocks, and a slow database must not stall the
+    /// dispatcher or the pollers sharing a worker with it.
+    async fn with_conn<T, F>(&self, query: F) -> StorageResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut PooledConn) -> StorageResult<T> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || query(&mut pool.get_conn()?)).await?
+    }
+}
+
+#[async_trait]
+impl Storage for MysqlStorage {
+    /// Applies pending schema migrations, see `migrations::run`.
+    async fn init(&self) -> StorageResult<()> {
+        self.with_conn(|conn| migrations::run(conn).map(|_| ()))
+            .await
+    }
+
+    async fn save_user_info(&self, user: UserInfo) -> StorageResult<()> {
+        self.with_conn(move |conn| {
+            let params = params! {
+                "user_id" => user.user_id,
+                "username" => &user.username,
+                "first_name" => &user.first_name,
+                "last_name" => &user.last_name,
+            };
+            conn.exec_drop(
+                r"INSERT INTO user_info 
+                  (user_id, username, first_name, last_name)
+                  VALUES (:user_id, :username, :first_name, :last_name)
+                  ON DUPLICATE KEY UPDATE
+                  username = :username,
+                  first_name = :first_name,
+                  last_name = :last_name",
+                params,
+            )?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn save_setting_opts(&self, opt: SettingOpts) -> StorageResult<()> {
+        self.with_conn(move |conn| {
+            let params = params! {
+                "id" => setting_opts_id(&opt),
+                "user_id" => &opt.user_id,
+                "group_chat_id" => &opt.group_chat_id,
+                "chain_id" => &opt.chain_id,
+                "token_address" => &opt.token_address,
+                "min_buy_amount" => opt.min_buy_amount,
+                "buy_step" => opt.buy_step,
+                "emoji" => &opt.emoji,
+                "media_toggle" => opt.media_toggle,
+                "media_file_id" => &opt.media_file_id,
+                "media_type" => &opt.media_type,
+                "tg_link" => &opt.tg_link,
+                "website_link" => &opt.website_link,
+                "twitter_link" => &opt.twitter_link,
+                "sell_alerts" => opt.sell_alerts,
+                "min_sell_amount" => opt.min_sell_amount,
+                "sell_emoji" => &opt.sell_emoji,
+                "sell_media_file_id" => &opt.sell_media_file_id,
+                "sell_media_type" => &opt.sell_media_type,
+                "buy_template" => &opt.buy_template,
+                "language" => &opt.language
+            };
+
+            if let Err(e) = conn.exec_drop(
+                r"INSERT INTO setting_opts 
+                  (id, user_id, group_chat_id, chain_id, token_address, min_buy_amount, buy_step, emoji, 
+                   media_toggle, media_file_id, media_type, tg_link, website_link, twitter_link,
+                   sell_alerts, min_sell_amount, sell_emoji, sell_media_file_id, sell_media_type,
+                   buy_template, language)
+                  VALUES 
+                  (:id, :user_id, :group_chat_id, :chain_id, :token_address, :min_buy_amount, :buy_step, :emoji,
+                   :media_toggle, :media_file_id, :media_type, :tg_link, :website_link, :twitter_link,
+                   :sell_alerts, :min_sell_amount, :sell_emoji, :sell_media_file_id, :sell_media_type,
+                   :buy_template, :language)
+                  ON DUPLICATE KEY UPDATE
+                  user_id = :user_id,
+                  group_chat_id = :group_chat_id,
+                  chain_id = :chain_id,
+                  token_address = :token_address,
+                  min_buy_amount = :min_buy_amount,
+                  buy_step = :buy_step,
+                  emoji = :emoji,
+                  media_toggle = :media_toggle, 
+                  media_file_id = :media_file_id,
+                  media_type = :media_type,
+                  tg_link = :tg_link,
+                  website_link = :website_link,
+                  twitter_link = :twitter_link,
+                  sell_alerts = :sell_alerts,
+                  min_sell_amount = :min_sell_amount,
+                  sell_emoji = :sell_emoji,
+                  sell_media_file_id = :sell_media_file_id,
+                  sell_media_type = :sell_media_type,
+                  buy_template = :buy_template,
+                  language = :language",
+                params,
+            ) {
+                log::error!("Failed to save the settings of {}: {}", opt.group_chat_id, e);
+                return Err(e.into());
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_setting_opt(
+        &self,
+        group_id: &str,
+        token_adr: &str,
+    ) -> StorageResult<Option<SettingOpts>> {
+        let (group_id, token_adr) = (group_id.to_string(), token_adr.to_string());
+        self.with_conn(move |conn| {
+            let result: Option<SettingOpts> = conn.exec_first(
+                format!(
+                    r"SELECT {}
+                      FROM setting_opts 
+                      WHERE token_address = ? AND group_chat_id = ?
+                      LIMIT 1",
+                    SETTING_OPTS_COLUMNS
+                ),
+                (token_adr, group_id),
+            )?;
+            Ok(result)
+        })
+        .await
+    }
+
+    async fn get_group_setting_opts(&self, group_id: &str) -> StorageResult<Vec<SettingOpts>> {
+        let group_id = group_id.to_string();
+        self.with_conn(move |conn| {
+            let rows: Vec<Row> = conn.exec(
+                format!(
+                    r"SELECT {}
+                      FROM setting_opts
+                      WHERE group_chat_id = ? AND token_address <> ''
+                      ORDER BY token_address",
+                    SETTING_OPTS_COLUMNS
+                ),
+                (group_id,),
+            )?;
+
+            rows.into_iter()
+                .map(|row| SettingOpts::from_row_opt(row).map_err(|e| e.into()))
+                .collect()
+        })
+        .await
+    }
+
+    async fn get_all_setting_opts(&self) -> StorageResult<Vec<StorageResult<SettingOpts>>> {
+        self.with_conn(|conn| {
+            let rows: Vec<Row> = conn.query(format!(
+                r"SELECT {}
+                  FROM setting_opts",
+                SETTING_OPTS_COLUMNS
+            ))?;
+
+            Ok(rows
+                .into_iter()
+                .map(|row| SettingOpts::from_row_opt(row).map_err(|e| e.into()))
+                .collect())
+        })
+        .await
+    }
+
+    async fn delete_setting_opt(&self, group_id: &str, token_address: &str) -> StorageResult<bool> {
+        let (group_id, token_address) = (group_id.to_string(), token_address.to_string());
+        self.with_conn(move |conn| {
+            conn.exec_drop(
+                r"DELETE FROM setting_opts 
+                  WHERE token_address = ? AND group_chat_id = ?",
+                (token_address, group_id),
+            )?;
+
+            // Check if any row was affected
+            let affected_rows = conn.affected_rows();
+            Ok(affected_rows > 0)
+        })
+        .await
+    }
+
+    async fn get_dialogue_state(&self, chat_id: i64) -> StorageResult<Option<String>> {
+        self.with_conn(move |conn| {
+            Ok(conn.exec_first(
+                r"SELECT CAST(state AS CHAR) FROM dialogue_state WHERE chat_id = ?",
+                (chat_id,),
+            )?)
+        })
+        .await
+    }
+
+    async fn save_dialogue_state(&self, chat_id: i64, state: String) -> StorageResult<()> {
+        self.with_conn(move |conn| {
+            conn.exec_drop(
+                r"INSERT INTO dialogue_state (chat_id, state)
+                  VALUES (:chat_id, :state)
+                  ON DUPLICATE KEY UPDATE state = :state",
+                params! {
+                    "chat_id" => chat_id,
+                    "state" => state,
+                },
+            )?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn remove_dialogue_state(&self, chat_id: i64) -> StorageResult<()> {
+        self.with_conn(move |conn| {
+            conn.exec_drop(r"DELETE FROM dialogue_state WHERE chat_id = ?", (chat_id,))?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+const SETTING_OPTS_COLUMNS: &str = r"
+    CAST(user_id AS CHAR) as user_id,
+    CAST(group_chat_id AS CHAR) as group_chat_id,
+    CAST(chain_id AS CHAR) as chain_id,
+    CAST(token_address AS CHAR) as token_address,
+    min_buy_amount,
+    buy_step,
+    CAST(emoji AS CHAR) as emoji,
+    media_toggle,
+    NULLIF(CAST(media_file_id AS CHAR), '') as media_file_id,
+    CAST(media_type AS CHAR) as media_type,
+    CAST(tg_link AS CHAR) as tg_link,
+    CAST(website_link AS CHAR) as website_link,
+    CAST(twitter_link AS CHAR) as twitter_link,
+    sell_alerts,
+    min_sell_amount,
+    CAST(sell_emoji AS CHAR) as sell_emoji,
+    NULLIF(CAST(sell_media_file_id AS CHAR), '') as sell_media_file_id,
+    CAST(COALESCE(sell_media_type, '') AS CHAR) as sell_media_type,
+    NULLIF(CAST(buy_template AS CHAR), '') as buy_template,
+    CAST(language AS CHAR) as language
+";
+
+fn take_column<T: FromValue>(row: &mut Row, name: &str) -> Option<T> {
+    row.take_opt(name).and_then(|value| value.ok())
+}
+
+impl FromRow for SettingOpts {
+    fn from_row_opt(row: Row) -> std::result::Result<Self, FromRowError> {
+        let mut columns = row.clone();
+        let setting_opts = (|| {
+            Some(SettingOpts {
+                user_id: take_column(&mut columns, "user_id")?,
+                group_chat_id: take_column(&mut columns, "group_chat_id")?,
+                chain_id: take_column(&mut columns, "chain_id")?,
+                token_address: take_column(&mut columns, "token_address")?,
+                min_buy_amount: take_column(&mut columns, "min_buy_amount")?,
+                buy_step: take_column(&mut columns, "buy_step")?,
+                emoji: take_column(&mut columns, "emoji")?,
+                media_toggle: take_column(&mut columns, "media_toggle")?,
+                media_file_id: take_column(&mut columns, "media_file_id")?,
+                media_type: take_column(&mut columns, "media_type")?,
+                tg_link: take_column(&mut columns, "tg_link")?,
+                website_link: take_column(&mut columns, "website_link")?,
+                twitter_link: take_column(&mut columns, "twitter_link")?,
+                sell_alerts: take_column(&mut columns, "sell_alerts")?,
+                min_sell_amount: take_column(&mut columns, "min_sell_amount")?,
+                sell_emoji: take_column(&mut columns, "sell_emoji")?,
+                sell_media_file_id: take_column(&mut columns, "sell_media_file_id")?,
+                sell_media_type: take_column(&mut columns, "sell_media_type")?,
+                buy_template: take_column(&mut columns, "buy_template")?,
+                language: take_column(&mut columns, "language")?,
+            })
+        })();
+        setting_opts.ok_or(FromRowError(row))
+    }
+}
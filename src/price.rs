@@ -0,0 +1,458 @@
+use async_trait::async_trait;
+use log::error;
+use reqwest::Client;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::chain::Chain;
+use crate::config::PriceConfig;
+use crate::get_dex_pairs;
+use crate::token_overview::TokenOverview;
+
+pub type PriceResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// A USD token price as a provider quoted it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TokenPrice {
+    pub usd: f64,
+    /// When the provider last priced the token, in Unix seconds. `None` for
+    /// providers that only quote live prices.
+    pub updated_at: Option<u64>,
+}
+
+impl TokenPrice {
+    pub fn live(usd: f64) -> Self {
+        Self {
+            usd,
+            updated_at: None,
+        }
+    }
+}
+
+/// A source of USD token prices.
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn token_price(&self, chain: &Chain, token_address: &str) -> PriceResult<TokenPrice>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceProviderKind {
+    Debank,
+    Birdeye,
+    DexScreener,
+}
+
+impl std::str::FromStr for PriceProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "debank" => Ok(Self::Debank),
+            "birdeye" => Ok(Self::Birdeye),
+            "dexscreener" => Ok(Self::DexScreener),
+            other => Err(format!(
+                "unknown price provider {:?}, expected debank, birdeye or dexscreener",
+                other
+            )),
+        }
+    }
+}
+
+/// When the swap itself is used to price a trade, see `swap::swap_price`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapPricing {
+    /// Only the price providers are used.
+    Off,
+    /// The swap is used when no provider has a price.
+    Fallback,
+    /// Offline mode: no price provider is asked.
+    Only,
+}
+
+impl std::str::FromStr for SwapPricing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "fallback" => Ok(Self::Fallback),
+            "only" => Ok(Self::Only),
+            other => Err(format!(
+                "unknown swap pricing {:?}, expected off, fallback or only",
+                other
+            )),
+        }
+    }
+}
+
+/// Builds the configured providers in order. Providers that need an API key
+/// are left out, with a warning, when the key is not set. No provider at all
+/// is only accepted when swaps can price the trades.
+pub fn price_providers(config: &PriceConfig) -> PriceResult<FallbackPriceProvider> {
+    if config.swap_pricing == SwapPricing::Only {
+        return Ok(FallbackPriceProvider::new(Vec::new(), config.max_age));
+    }
+    let client = Client::builder().timeout(config.timeout).build()?;
+
+    let mut providers: Vec<Box<dyn PriceProvider>> = Vec::new();
+    for kind in &config.providers {
+        match kind {
+            PriceProviderKind::Debank => match &config.debank_api_key {
+                Some(api_key) => providers.push(Box::new(DebankProvider::new(
+                    client.clone(),
+                    api_key.clone(),
+                ))),
+                None => log::warn!("DEBANK_API_KEY is not set, skipping DeBank prices"),
+            },
+            PriceProviderKind::Birdeye => match &config.birdeye_api_key {
+                Some(api_key) => providers.push(Box::new(BirdeyeProvider::new(
+                    client.clone(),
+                    api_key.clone(),
+                ))),
+                None => log::warn!("BIRDEYE_API_KEY is not set, skipping Birdeye prices"),
+            },
+            PriceProviderKind::DexScreener => {
+                providers.push(Box::new(DexScreenerProvider::new(client.clone())))
+            }
+        }
+    }
+
+    if providers.is_empty() && config.swap_pricing == SwapPricing::Off {
+        return Err("no price provider is usable, check PRICE_PROVIDERS and the API keys".into());
+    }
+    Ok(FallbackPriceProvider::new(providers, config.max_age))
+}
+
+/// Asks each provider in turn and returns the first price found, so one
+/// failing provider never stops the alerts. A price older than `max_age`
+/// counts as none, which lets the swap price the trade instead.
+pub struct FallbackPriceProvider {
+    providers: Vec<Box<dyn PriceProvider>>,
+    max_age: Duration,
+}
+
+impl FallbackPriceProvider {
+    pub fn new(providers: Vec<Box<dyn PriceProvider>>, max_age: Duration) -> Self {
+        Self { providers, max_age }
+    }
+
+    /// The age of the price, `None` if the provider did not date it.
+    fn age(price: &TokenPrice) -> Option<Duration> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+        Some(now.saturating_sub(Duration::from_secs(price.updated_at?)))
+    }
+}
+
+#[async_trait]
+impl PriceProvider for FallbackPriceProvider {
+    fn name(&self) -> &'static str {
+        "fallback"
+    }
+
+    async fn token_price(&self, chain: &Chain, token_address: &str) -> PriceResult<TokenPrice> {
+        for provider in &self.providers {
+            match provider.token_price(chain, token_address).await {
+                Ok(price) if !price.usd.is_finite() || price.usd <= 0.0 => log::warn!(
+                    "{} returned no usable price ({}) for {} on {}",
+                    provider.name(),
+                    price.usd,
+                    token_address,
+                    chain.name
+                ),
+                Ok(price) => match Self::age(&price) {
+                    Some(age) if age > self.max_age => log::warn!(
+                        "{} has a stale price for {} on {}, {}s old",
+                        provider.name(),
+                        token_address,
+                        chain.name,
+                        age.as_secs()
+                    ),
+                    _ => return Ok(price),
+                },
+                Err(e) => log::warn!(
+                    "{} has no price for {} on {}: {}",
+                    provider.name(),
+                    token_address,
+                    chain.name,
+                    e
+                ),
+            }
+        }
+        Err(format!(
+            "no price provider has a price for {} on {}",
+            token_address, chain.name
+        )
+        .into())
+    }
+}
+
+pub struct DebankProvider {
+    client: Client,
+    api_key: String,
+}
+
+impl DebankProvider {
+    pub fn new(client: Client, api_key: String) -> Self {
+        Self { client, api_key }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for DebankProvider {
+    fn name(&self) -> &'static str {
+        "DeBank"
+    }
+
+    async fn token_price(&self, chain: &Chain, token_address: &str) -> PriceResult<TokenPrice> {
+        let url = format!(
+            "https://pro-openapi.debank.com/v1/token?chain_id={}&id={}",
+            chain.debank_chain_id, token_address
+        );
+        let response = self
+            .client
+            .get(&url)
+            .header("Accesskey", &self.api_key)
+            .send()
+            .await?
+            .error_for_status()?;
+        let text = response.text().await?;
+        match serde_json::from_str::<TokenOverview>(&text) {
+            Ok(token_overview) => Ok(debank_price(&token_overview)),
+            Err(e) => {
+                error!("Deserialization error: {}", e);
+                Err(Box::new(e))
+            }
+        }
+    }
+}
+
+/// DeBank quotes current prices. Its `time_at` is when the token was
+/// created, not when it was priced, so the price is not dated.
+fn debank_price(token_overview: &TokenOverview) -> TokenPrice {
+    TokenPrice::live(token_overview.price)
+}
+
+pub struct BirdeyeProvider {
+    client: Client,
+    api_key: String,
+}
+
+impl BirdeyeProvider {
+    pub fn new(client: Client, api_key: String) -> Self {
+        Self { client, api_key }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct BirdeyePrice {
+    success: bool,
+    data: Option<BirdeyePriceData>,
+}
+
+#[derive(serde::Deserialize)]
+struct BirdeyePriceData {
+    value: f64,
+    #[serde(rename = "updateUnixTime", default)]
+    update_unix_time: Option<u64>,
+}
+
+#[async_trait]
+impl PriceProvider for BirdeyeProvider {
+    fn name(&self) -> &'static str {
+        "Birdeye"
+    }
+
+    async fn token_price(&self, chain: &Chain, token_address: &str) -> PriceResult<TokenPrice> {
+        let birdeye_chain_id = chain
+            .birdeye_chain_id
+            .ok_or_else(|| format!("Birdeye does not cover {}", chain.name))?;
+        let response = self
+            .client
+            .get("https://public-api.birdeye.so/defi/price")
+            .query(&[("address", token_address)])
+            .header("X-API-KEY", &self.api_key)
+            .header("x-chain", birdeye_chain_id)
+            .send()
+            .await?
+            .error_for_status()?;
+        let price: BirdeyePrice = serde_json::from_str(&response.text().await?)?;
+        match price.data {
+            Some(data) if price.success => Ok(TokenPrice {
+                usd: data.value,
+                updated_at: data.update_unix_time,
+            }),
+            _ => Err("Birdeye returned no price".into()),
+        }
+    }
+}
+
+/// Prices from the most liquid DexScreener pair quoting the token.
+pub struct DexScreenerProvider {
+    client: Client,
+}
+
+impl DexScreenerProvider {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for DexScreenerProvider {
+    fn name(&self) -> &'static str {
+        "DexScreener"
+    }
+
+    async fn token_price(&self, chain: &Chain, token_address: &str) -> PriceResult<TokenPrice> {
+        let dex_pairs = get_dex_pairs(self.client.clone(), token_address).await?;
+        dex_pairs
+            .pairs
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|pair| {
+                pair.chain_id == chain.dexscreener_chain_id
+                    && pair.base_token.address.eq_ignore_ascii_case(token_address)
+            })
+            .filter_map(|pair| {
+                let price = pair.price_usd.as_deref()?.parse::<f64>().ok()?;
+                let liquidity = pair.liquidity.and_then(|l| l.usd).unwrap_or(0.0);
+                Some((liquidity, price))
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, price)| TokenPrice::live(price))
+            .ok_or_else(|| "DexScreener lists no priced pair".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::find_chain;
+
+    const MAX_AGE: Duration = Duration::from_secs(300);
+
+    struct Fixed(&'static str, PriceResult<f64>);
+
+    /// A price the provider last updated `.2` seconds ago.
+    struct Dated(&'static str, f64, u64);
+
+    #[async_trait]
+    impl PriceProvider for Fixed {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn token_price(&self, _: &Chain, _: &str) -> PriceResult<TokenPrice> {
+            match &self.1 {
+                Ok(price) => Ok(TokenPrice::live(*price)),
+                Err(e) => Err(e.to_string().into()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl PriceProvider for Dated {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn token_price(&self, _: &Chain, _: &str) -> PriceResult<TokenPrice> {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+            Ok(TokenPrice {
+                usd: self.1,
+                updated_at: Some(now.as_secs() - self.2),
+            })
+        }
+    }
+
+    fn chain() -> &'static Chain {
+        find_chain("apechain").unwrap()
+    }
+
+    #[tokio::test]
+    async fn falls_back_past_failing_and_unusable_providers() {
+        let prices = FallbackPriceProvider::new(
+            vec![
+                Box::new(Fixed("down", Err("timeout".into()))),
+                Box::new(Fixed("zero", Ok(0.0))),
+                Box::new(Fixed("good", Ok(0.25))),
+                Box::new(Fixed("later", Ok(9.0))),
+            ],
+            MAX_AGE,
+        );
+
+        assert_eq!(prices.token_price(chain(), "0x1").await.unwrap().usd, 0.25);
+    }
+
+    #[tokio::test]
+    async fn fails_when_every_provider_fails() {
+        let prices = FallbackPriceProvider::new(
+            vec![
+                Box::new(Fixed("down", Err("timeout".into()))),
+                Box::new(Fixed("nan", Ok(f64::NAN))),
+                Box::new(Dated("stale", 0.25, 3600)),
+            ],
+            MAX_AGE,
+        );
+
+        assert!(prices.token_price(chain(), "0x1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn skips_stale_prices() {
+        let prices = FallbackPriceProvider::new(
+            vec![
+                Box::new(Dated("stale", 0.25, 3600)),
+                Box::new(Dated("fresh", 0.5, 60)),
+            ],
+            MAX_AGE,
+        );
+        assert_eq!(prices.token_price(chain(), "0x1").await.unwrap().usd, 0.5);
+
+        let patient = FallbackPriceProvider::new(
+            vec![Box::new(Dated("stale", 0.25, 3600))],
+            Duration::from_secs(7200),
+        );
+        assert_eq!(patient.token_price(chain(), "0x1").await.unwrap().usd, 0.25);
+    }
+
+    #[tokio::test]
+    async fn old_debank_tokens_are_not_stale() {
+        struct Debanked(TokenOverview);
+
+        #[async_trait]
+        impl PriceProvider for Debanked {
+            fn name(&self) -> &'static str {
+                "DeBank"
+            }
+
+            async fn token_price(&self, _: &Chain, _: &str) -> PriceResult<TokenPrice> {
+                Ok(debank_price(&self.0))
+            }
+        }
+
+        let prices = FallbackPriceProvider::new(
+            vec![Box::new(Debanked(TokenOverview {
+                price: 0.25,
+                time_at: 1_600_000_000.0,
+                ..Default::default()
+            }))],
+            MAX_AGE,
+        );
+
+        assert_eq!(
+            prices.token_price(chain(), "0x1").await.unwrap(),
+            TokenPrice::live(0.25)
+        );
+    }
+
+    #[test]
+    fn parses_provider_names() {
+        assert_eq!(
+            " DexScreener".parse::<PriceProviderKind>(),
+            Ok(PriceProviderKind::DexScreener)
+        );
+        assert!("coingecko".parse::<PriceProviderKind>().is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use crate::setting_opts::SettingOpts;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SessionKey {
+    pub user_id: String,
+    pub group_chat_id: String,
+}
+
+/// Configuration drafts, one per (Telegram user, target group).
+///
+/// An admin opens a session by running `/settings` in a group. Everything they
+/// do afterwards in the private chat edits the draft of that session only.
+#[derive(Default)]
+pub struct SessionStore {
+    drafts: RwLock<HashMap<SessionKey, SettingOpts>>,
+    active_groups: RwLock<HashMap<String, String>>,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes `group_chat_id` the group the user is configuring and creates an
+    /// empty draft for it if there is none yet.
+    pub async fn open(&self, user_id: &str, group_chat_id: &str) {
+        let key = SessionKey {
+            user_id: user_id.to_string(),
+            group_chat_id: group_chat_id.to_string(),
+        };
+        self.drafts
+            .write()
+            .await
+            .entry(key)
+            .or_insert_with(|| SettingOpts {
+                user_id: user_id.to_string(),
+                group_chat_id: group_chat_id.to_string(),
+                ..SettingOpts::default()
+            });
+        self.active_groups
+            .write()
+            .await
+            .insert(user_id.to_string(), group_chat_id.to_string());
+    }
+
+    pub async fn active_key(&self, user_id: &str) -> Option<SessionKey> {
+        self.active_groups
+            .read()
+            .await
+            .get(user_id)
+            .map(|group_chat_id| SessionKey {
+                user_id: user_id.to_string(),
+                group_chat_id: group_chat_id.clone(),
+            })
+    }
+
+    /// Returns a copy of the draft the user is currently editing.
+    pub async fn get(&self, user_id: &str) -> Option<SettingOpts> {
+        let key = self.active_key(user_id).await?;
+        self.drafts.read().await.get(&key).cloned()
+    }
+
+    /// Replaces the active draft. The user and group ids of the session always
+    /// win over the ones in `opts`.
+    pub async fn replace(&self, user_id: &str, opts: SettingOpts) -> Option<SettingOpts> {
+        self.update(user_id, |draft| *draft = opts).await
+    }
+
+    /// Applies `f` to the active draft and returns the updated copy.
+    pub async fn update<F>(&self, user_id: &str, f: F) -> Option<SettingOpts>
+    where
+        F: FnOnce(&mut SettingOpts),
+    {
+        let key = self.active_key(user_id).await?;
+        let mut drafts = self.drafts.write().await;
+        let draft = drafts.get_mut(&key)?;
+        f(draft);
+        draft.user_id = key.user_id;
+        draft.group_chat_id = key.group_chat_id;
+        Some(draft.clone())
+    }
+
+    /// Ends the session and throws its draft away, e.g. once the user is no
+    /// longer allowed to configure the group.
+    pub async fn close(&self, user_id: &str) {
+        if let Some(key) = self.active_key(user_id).await {
+            self.drafts.write().await.remove(&key);
+            self.active_groups.write().await.remove(user_id);
+        }
+    }
+
+    /// Drops the token from the active draft, keeping the session open and
+    /// the language the draft speaks.
+    pub async fn reset(&self, user_id: &str) -> Option<SettingOpts> {
+        self.update(user_id, |draft| {
+            *draft = SettingOpts {
+                language: std::mem::take(&mut draft.language),
+                ..SettingOpts::default()
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn opening_another_group_switches_the_draft() {
+        let sessions = SessionStore::new();
+        assert!(sessions.get("1").await.is_none());
+
+        sessions.open("1", "-10").await;
+        sessions
+            .update("1", |draft| draft.emoji = "🚀".to_string())
+            .await;
+        sessions.open("1", "-20").await;
+
+        let draft = sessions.get("1").await.unwrap();
+        assert_eq!(draft.group_chat_id, "-20");
+        assert_eq!(draft.emoji, SettingOpts::default().emoji);
+
+        // Coming back to the first group picks up its draft where it was left
+        sessions.open("1", "-10").await;
+        assert_eq!(sessions.active_key("1").await.unwrap().group_chat_id, "-10");
+        assert_eq!(sessions.get("1").await.unwrap().emoji, "🚀");
+    }
+
+    #[tokio::test]
+    async fn sessions_of_other_groups_and_users_stay_apart() {
+        let sessions = SessionStore::new();
+        sessions.open("1", "-10").await;
+        sessions.open("2", "-10").await;
+        sessions.open("2", "-20").await;
+
+        let hijack = SettingOpts {
+            user_id: "2".to_string(),
+            group_chat_id: "-20".to_string(),
+            min_buy_amount: 500.0,
+            ..SettingOpts::default()
+        };
+        let draft = sessions.replace("1", hijack).await.unwrap();
+        assert_eq!(
+            (draft.user_id.as_str(), draft.group_chat_id.as_str()),
+            ("1", "-10")
+        );
+        assert_eq!(sessions.get("2").await.unwrap().min_buy_amount, 0.0);
+
+        sessions.close("2").await;
+        assert!(sessions.get("2").await.is_none());
+        assert!(sessions
+            .update("2", |draft| draft.buy_step = 5)
+            .await
+            .is_none());
+        assert_eq!(sessions.get("1").await.unwrap().min_buy_amount, 500.0);
+
+        // The draft of the other group was never touched
+        sessions.open("2", "-10").await;
+        assert_eq!(sessions.get("2").await.unwrap().min_buy_amount, 0.0);
+    }
+}
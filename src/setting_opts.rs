@@ -1,9 +1,14 @@
 use serde::{ Serialize, Deserialize };
 
+use crate::chain::DEFAULT_CHAIN_ID;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SettingOpts {
+    /// The admin who last changed the settings. The token belongs to the
+    /// group, any of its admins may change it.
     pub user_id: String,
     pub group_chat_id: String,
+    pub chain_id: String,
     pub token_address: String,
     pub min_buy_amount: f64,
     pub buy_step: i32,
@@ -14,6 +19,16 @@ pub struct SettingOpts {
     pub tg_link: String,
     pub twitter_link: String,
     pub website_link: String,
+    pub sell_alerts: bool,
+    pub min_sell_amount: f64,
+    pub sell_emoji: String,
+    pub sell_media_type: String,
+    pub sell_media_file_id: Option<String>,
+    /// Buy alert template, `None` for `template::default_buy_template`.
+    pub buy_template: Option<String>,
+    /// Language code of the settings keyboard and the alerts, e.g. `de`.
+    /// Empty for a token that is not saved yet, which reads as English.
+    pub language: String,
 }
 
 impl Default for SettingOpts {
@@ -21,6 +36,7 @@ impl Default for SettingOpts {
         Self {
             user_id: String::new(),
             group_chat_id: String::new(),
+            chain_id: DEFAULT_CHAIN_ID.to_string(),
             token_address: String::new(),
             min_buy_amount: 0.0,
             buy_step: 30,
@@ -31,6 +47,13 @@ impl Default for SettingOpts {
             tg_link: String::new(),
             twitter_link: String::new(),
             website_link: String::new(),
+            sell_alerts: false,
+            min_sell_amount: 0.0,
+            sell_emoji: "🔴".to_string(),
+            sell_media_type: String::new(),
+            sell_media_file_id: Some(String::new()),
+            buy_template: None,
+            language: String::new(),
         }
     }
 }
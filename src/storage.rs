@@ -0,0 +1,277 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use crate::setting_opts::SettingOpts;
+use crate::user_info::UserInfo;
+
+pub type StorageResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Everything the bot persists. Handlers only talk to this trait, so they run
+/// the same against MySQL in production and `MemoryStorage` in tests.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Prepares the backing store, e.g. applies pending schema migrations.
+    async fn init(&self) -> StorageResult<()>;
+
+    async fn save_user_info(&self, user: UserInfo) -> StorageResult<()>;
+
+    async fn save_setting_opts(&self, opts: SettingOpts) -> StorageResult<()>;
+
+    /// Returns the saved settings of the group's token, `None` if the group
+    /// does not track it.
+    async fn find_setting_opt(
+        &self,
+        group_id: &str,
+        token_address: &str,
+    ) -> StorageResult<Option<SettingOpts>>;
+
+    /// Returns the saved settings, or defaults for the group and token when
+    /// nothing is saved yet.
+    async fn get_setting_opt(
+        &self,
+        group_id: &str,
+        token_address: &str,
+    ) -> StorageResult<SettingOpts> {
+        let saved = self.find_setting_opt(group_id, token_address).await?;
+        Ok(saved.unwrap_or_else(|| SettingOpts {
+            group_chat_id: group_id.to_string(),
+            token_address: token_address.to_string(),
+            ..SettingOpts::default()
+        }))
+    }
+
+    /// Returns every token the group tracks, whichever admin added it,
+    /// ordered by address.
+    async fn get_group_setting_opts(&self, group_id: &str) -> StorageResult<Vec<SettingOpts>>;
+
+    /// Returns every saved row. Rows that cannot be read are returned as
+    /// errors so callers can skip them and go on with the rest.
+    async fn get_all_setting_opts(&self) -> StorageResult<Vec<StorageResult<SettingOpts>>>;
+
+    /// Returns `true` if a row was deleted.
+    async fn delete_setting_opt(&self, group_id: &str, token_address: &str) -> StorageResult<bool>;
+
+    /// The serialized dialogue of a private chat, if it is in one.
+    async fn get_dialogue_state(&self, chat_id: i64) -> StorageResult<Option<String>>;
+
+    async fn save_dialogue_state(&self, chat_id: i64, state: String) -> StorageResult<()>;
+
+    /// Removing a chat that is in no dialogue is not an error.
+    async fn remove_dialogue_state(&self, chat_id: i64) -> StorageResult<()>;
+}
+
+/// A token's settings belong to its group, so every admin of the group edits
+/// the same row.
+pub fn setting_opts_id(opts: &SettingOpts) -> String {
+    format!("{}/{}", opts.group_chat_id, opts.token_address)
+}
+
+/// Keeps everything in process memory. Nothing survives a restart.
+#[derive(Default)]
+pub struct MemoryStorage {
+    users: RwLock<HashMap<String, UserInfo>>,
+    setting_opts: RwLock<HashMap<String, SettingOpts>>,
+    dialogue_states: RwLock<HashMap<i64, String>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn init(&self) -> StorageResult<()> {
+        Ok(())
+    }
+
+    async fn save_user_info(&self, user: UserInfo) -> StorageResult<()> {
+        self.users.write().await.insert(user.user_id.clone(), user);
+        Ok(())
+    }
+
+    async fn save_setting_opts(&self, opts: SettingOpts) -> StorageResult<()> {
+        self.setting_opts
+            .write()
+            .await
+            .insert(setting_opts_id(&opts), opts);
+        Ok(())
+    }
+
+    async fn find_setting_opt(
+        &self,
+        group_id: &str,
+        token_address: &str,
+    ) -> StorageResult<Option<SettingOpts>> {
+        let opts = SettingOpts {
+            group_chat_id: group_id.to_string(),
+            token_address: token_address.to_string(),
+            ..SettingOpts::default()
+        };
+        Ok(self
+            .setting_opts
+            .read()
+            .await
+            .get(&setting_opts_id(&opts))
+            .cloned())
+    }
+
+    async fn get_group_setting_opts(&self, group_id: &str) -> StorageResult<Vec<SettingOpts>> {
+        let mut group_setting_opts: Vec<SettingOpts> = self
+            .setting_opts
+            .read()
+            .await
+            .values()
+            .filter(|opts| opts.group_chat_id == group_id && !opts.token_address.is_empty())
+            .cloned()
+            .collect();
+        group_setting_opts.sort_by(|a, b| a.token_address.cmp(&b.token_address));
+        Ok(group_setting_opts)
+    }
+
+    async fn get_all_setting_opts(&self) -> StorageResult<Vec<StorageResult<SettingOpts>>> {
+        Ok(self
+            .setting_opts
+            .read()
+            .await
+            .values()
+            .cloned()
+            .map(Ok)
+            .collect())
+    }
+
+    async fn delete_setting_opt(&self, group_id: &str, token_address: &str) -> StorageResult<bool> {
+        let opts = SettingOpts {
+            group_chat_id: group_id.to_string(),
+            token_address: token_address.to_string(),
+            ..SettingOpts::default()
+        };
+        Ok(self
+            .setting_opts
+            .write()
+            .await
+            .remove(&setting_opts_id(&opts))
+            .is_some())
+    }
+
+    async fn get_dialogue_state(&self, chat_id: i64) -> StorageResult<Option<String>> {
+        Ok(self.dialogue_states.read().await.get(&chat_id).cloned())
+    }
+
+    async fn save_dialogue_state(&self, chat_id: i64, state: String) -> StorageResult<()> {
+        self.dialogue_states.write().await.insert(chat_id, state);
+        Ok(())
+    }
+
+    async fn remove_dialogue_state(&self, chat_id: i64) -> StorageResult<()> {
+        self.dialogue_states.write().await.remove(&chat_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(user_id: &str, group_id: &str, token_address: &str) -> SettingOpts {
+        SettingOpts {
+            user_id: user_id.to_string(),
+            group_chat_id: group_id.to_string(),
+            token_address: token_address.to_string(),
+            ..SettingOpts::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn get_setting_opt_falls_back_to_defaults() {
+        let storage = MemoryStorage::new();
+        let loaded = storage.get_setting_opt("-10", "0xabc").await.unwrap();
+
+        assert_eq!(loaded.group_chat_id, "-10");
+        assert_eq!(loaded.token_address, "0xabc");
+        assert_eq!(loaded.buy_step, SettingOpts::default().buy_step);
+        assert!(storage
+            .find_setting_opt("-10", "0xabc")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn save_setting_opts_overwrites_the_same_row() {
+        let storage = MemoryStorage::new();
+        let mut saved = opts("1", "-10", "0xabc");
+        saved.min_buy_amount = 5.0;
+        storage.save_setting_opts(saved.clone()).await.unwrap();
+        saved.min_buy_amount = 10.0;
+        storage.save_setting_opts(saved).await.unwrap();
+
+        let all = storage.get_all_setting_opts().await.unwrap();
+        assert_eq!(all.len(), 1);
+        let loaded = storage.get_setting_opt("-10", "0xabc").await.unwrap();
+        assert_eq!(loaded.min_buy_amount, 10.0);
+    }
+
+    #[tokio::test]
+    async fn admins_of_a_group_share_its_tokens() {
+        let storage = MemoryStorage::new();
+        storage
+            .save_setting_opts(opts("1", "-10", "0xabc"))
+            .await
+            .unwrap();
+        let mut edited = opts("2", "-10", "0xabc");
+        edited.emoji = "🚀".to_string();
+        storage.save_setting_opts(edited).await.unwrap();
+        storage
+            .save_setting_opts(opts("2", "-10", "0x123"))
+            .await
+            .unwrap();
+        storage
+            .save_setting_opts(opts("1", "-20", "0xdef"))
+            .await
+            .unwrap();
+
+        let tokens = storage.get_group_setting_opts("-10").await.unwrap();
+        let addresses: Vec<&str> = tokens
+            .iter()
+            .map(|opts| opts.token_address.as_str())
+            .collect();
+        assert_eq!(addresses, ["0x123", "0xabc"]);
+        assert_eq!(tokens[1].emoji, "🚀");
+        assert_eq!(tokens[1].user_id, "2");
+    }
+
+    #[tokio::test]
+    async fn groups_do_not_share_settings() {
+        let storage = MemoryStorage::new();
+        let mut first = opts("1", "-10", "0xabc");
+        first.emoji = "🚀".to_string();
+        storage.save_setting_opts(first).await.unwrap();
+        storage
+            .save_setting_opts(opts("1", "-20", "0xabc"))
+            .await
+            .unwrap();
+
+        let second = storage.get_setting_opt("-20", "0xabc").await.unwrap();
+        assert_eq!(second.emoji, SettingOpts::default().emoji);
+    }
+
+    #[tokio::test]
+    async fn delete_only_removes_the_matching_group() {
+        let storage = MemoryStorage::new();
+        storage
+            .save_setting_opts(opts("1", "-10", "0xabc"))
+            .await
+            .unwrap();
+        storage
+            .save_setting_opts(opts("1", "-20", "0xabc"))
+            .await
+            .unwrap();
+
+        assert!(storage.delete_setting_opt("-10", "0xabc").await.unwrap());
+        assert!(!storage.delete_setting_opt("-10", "0xabc").await.unwrap());
+        assert_eq!(storage.get_all_setting_opts().await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::amount::{AmountError, TokenAmount};
+use crate::chain::Chain;
+use crate::explorer::{BlockExplorer, ExplorerResult};
+use crate::token_transfer::TokenTransferItem;
+use crate::transfer_kind::TransferKind;
+use crate::tx_info::TxInfo;
+
+/// How long a native/USD rate read from the explorer is reused.
+const NATIVE_USD_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Native/USD rates per chain, read from the explorer and reused for a few
+/// minutes so pricing a swap costs no extra request most of the time.
+#[derive(Default)]
+pub struct NativeUsdCache {
+    rates: RwLock<HashMap<&'static str, (Instant, f64)>>,
+}
+
+impl NativeUsdCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn get(
+        &self,
+        explorer: &dyn BlockExplorer,
+        chain: &'static Chain,
+    ) -> ExplorerResult<f64> {
+        if let Some((read_at, rate)) = self.rates.read().await.get(chain.id) {
+            if read_at.elapsed() < NATIVE_USD_TTL {
+                return Ok(*rate);
+            }
+        }
+        let rate = explorer.native_usd_price().await?;
+        self.rates
+            .write()
+            .await
+            .insert(chain.id, (Instant::now(), rate));
+        Ok(rate)
+    }
+}
+
+/// Native amount paid for a buy or received for a sell. A buy paid in the
+/// native coin carries it as the transaction's `value`; otherwise the
+/// wrapped-native leg through one of the token's pairs is used. `None` if the
+/// swap has no native side, e.g. the token was bought with a stablecoin.
+pub async fn native_amount(
+    explorer: &dyn BlockExplorer,
+    chain: &Chain,
+    transfer: &TokenTransferItem,
+    kind: TransferKind,
+    pairs: &HashSet<String>,
+    tx_info: Option<&TxInfo>,
+) -> ExplorerResult<Option<TokenAmount>> {
+    if kind == TransferKind::Buy {
+        if let Some(tx_info) = tx_info {
+            let value = TokenAmount::native(&tx_info.value)?;
+            if !value.is_zero() {
+                return Ok(Some(value));
+            }
+        }
+    }
+    let tx_transfers = explorer.tx_token_transfers(&transfer.tx_hash).await?;
+    Ok(wrapped_native_leg(chain, &tx_transfers.items, kind, pairs)?)
+}
+
+/// Derives the token's USD price from a swap: the native amount paid (or
+/// received) divided by the token amount, times the native/USD rate. Works
+/// for tokens no price API has indexed yet.
+pub async fn swap_price(
+    explorer: &dyn BlockExplorer,
+    chain: &'static Chain,
+    native_amount: Option<TokenAmount>,
+    token_amount: TokenAmount,
+    native_usd: &NativeUsdCache,
+) -> ExplorerResult<f64> {
+    let native_per_token = native_amount
+        .and_then(|native_amount| native_per_token(native_amount, token_amount))
+        .ok_or("the swap has no native leg")?;
+    Ok(native_per_token * native_usd.get(explorer, chain).await?)
+}
+
+/// Sum of the wrapped-native transfers into a pair for a buy, or out of a
+/// pair for a sell.
+pub fn wrapped_native_leg(
+    chain: &Chain,
+    tx_transfers: &[TokenTransferItem],
+    kind: TransferKind,
+    pairs: &HashSet<String>,
+) -> Result<Option<TokenAmount>, AmountError> {
+    let legs = tx_transfers
+        .iter()
+        .filter(|item| {
+            item.token
+                .address
+                .eq_ignore_ascii_case(chain.wrapped_native)
+        })
+        .filter(|item| match kind {
+            TransferKind::Buy => pairs.contains(&item.to.hash.to_lowercase()),
+            TransferKind::Sell => pairs.contains(&item.from.hash.to_lowercase()),
+            _ => false,
+        })
+        .map(TokenTransferItem::amount)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut legs = legs.into_iter();
+    let Some(first) = legs.next() else {
+        return Ok(None);
+    };
+    legs.try_fold(first, |sum, leg| sum.checked_add(leg))
+        .map(Some)
+        .ok_or_else(|| AmountError::from("wrapped native legs cannot be added up"))
+}
+
+pub fn native_per_token(native_amount: TokenAmount, token_amount: TokenAmount) -> Option<f64> {
+    let price = native_amount.to_f64() / token_amount.to_f64();
+    (price.is_finite() && price > 0.0).then_some(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::find_chain;
+    use crate::token_transfer::{AddressInfo, TokenInfo, Total};
+
+    const PAIR: &str = "0x3b1e2c9d8f7a6b5c4d3e2f1a0b9c8d7e6f5a4b3c";
+    const ROUTER: &str = "0xc0ffee0000000000000000000000000000000004";
+
+    fn leg(token: &str, from: &str, to: &str, value: &str) -> TokenTransferItem {
+        TokenTransferItem {
+            from: AddressInfo {
+                hash: from.to_string(),
+                ..AddressInfo::default()
+            },
+            to: AddressInfo {
+                hash: to.to_string(),
+                ..AddressInfo::default()
+            },
+            token: TokenInfo {
+                address: token.to_string(),
+                ..TokenInfo::default()
+            },
+            total: Total {
+                decimals: "18".to_string(),
+                value: value.to_string(),
+            },
+            ..TokenTransferItem::default()
+        }
+    }
+
+    fn amount(raw: &str) -> TokenAmount {
+        TokenAmount::parse(raw, "18").unwrap()
+    }
+
+    fn pairs() -> HashSet<String> {
+        HashSet::from([PAIR.to_string()])
+    }
+
+    #[test]
+    fn buys_use_the_wrapped_native_paid_into_the_pair() {
+        let chain = find_chain("apechain").unwrap();
+        let legs = vec![
+            leg(chain.wrapped_native, ROUTER, PAIR, "1250000000000000000000"),
+            leg(
+                "0x7f9a6a9e2b6c0b8d1c3e5f4a2b1c0d9e8f7a6b5c",
+                PAIR,
+                ROUTER,
+                "1",
+            ),
+        ];
+
+        assert_eq!(
+            wrapped_native_leg(chain, &legs, TransferKind::Buy, &pairs()),
+            Ok(Some(amount("1250000000000000000000")))
+        );
+        assert_eq!(
+            wrapped_native_leg(chain, &legs, TransferKind::Sell, &pairs()),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn sells_use_the_wrapped_native_paid_out_of_the_pair() {
+        let chain = find_chain("apechain").unwrap();
+        let legs = vec![leg(
+            &chain.wrapped_native.to_uppercase().replace("0X", "0x"),
+            PAIR,
+            ROUTER,
+            "500000000000000000",
+        )];
+
+        assert_eq!(
+            wrapped_native_leg(chain, &legs, TransferKind::Sell, &pairs()),
+            Ok(Some(amount("500000000000000000")))
+        );
+    }
+
+    #[test]
+    fn unparsable_legs_are_errors() {
+        let chain = find_chain("apechain").unwrap();
+        let legs = vec![leg(chain.wrapped_native, ROUTER, PAIR, "1.5e21")];
+
+        assert!(wrapped_native_leg(chain, &legs, TransferKind::Buy, &pairs()).is_err());
+    }
+
+    #[test]
+    fn native_per_token_needs_both_legs() {
+        assert_eq!(
+            native_per_token(
+                amount("1250000000000000000000"),
+                amount("1500000000000000000000000")
+            ),
+            Some(1250.0 / 1_500_000.0)
+        );
+        assert_eq!(
+            native_per_token(amount("0"), amount("1500000000000000000000000")),
+            None
+        );
+        assert_eq!(
+            native_per_token(amount("1250000000000000000000"), amount("0")),
+            None
+        );
+    }
+}
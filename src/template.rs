@@ -0,0 +1,700 @@
+//! Alert templates with named placeholders, e.g.
+//! `{emoji_bar}\n💰 Got: {got_amount} ${symbol}`.
+//!
+//! `{{` and `}}` stand for literal braces. A line with a placeholder that has
+//! no value for the trade, such as `{gas}` on a sell, is left out.
+//!
+//! Templates are Telegram HTML. Placeholder values are escaped, and a link
+//! written `<a href="{tg_link}">TG</a>` is left out, with its ` | `
+//! separator, when the link is empty or invalid.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::html::{self, MarkupError};
+use crate::i18n::Language;
+
+/// The buy alert layout groups get until they save their own, with labels in
+/// the group's language.
+pub fn default_buy_template(language: Language) -> String {
+    let label = |id: &str| html::escape(&language.text(id));
+    format!(
+        "{{emoji_bar}}
+
+💲 {spent}: ${{spent_usd}}{{spent_native}}
+💰 {got}: {{got_amount}} ${{symbol}}
+⛽ {gas}: {{gas}}
+✅ {dex}: <a href=\"{{dex_link}}\">{{dex_name}}</a> | \
+<a href=\"{{trending_link}}\">🔖 {trending}</a> - \
+<a href=\"{{ads_link}}\">{ads}</a>
+🏷️ {price}: ${{price}}
+📊 {mcap}: ${{mcap}}
+
+{links}",
+        spent = label("alert-spent"),
+        got = label("alert-got"),
+        gas = label("alert-gas"),
+        dex = label("alert-dex"),
+        trending = label("alert-book-trending"),
+        ads = label("alert-ads"),
+        price = label("alert-price"),
+        mcap = label("alert-marketcap"),
+        links = link_line(language),
+    )
+}
+
+/// The sell alert layout. Sell alerts are not customizable.
+pub fn default_sell_template(language: Language) -> String {
+    let label = |id: &str| html::escape(&language.text(id));
+    format!(
+        "{{emoji_bar}}
+
+🔻 {sold}: {{amount}} ${{symbol}}
+💲 {value}: ${{value_usd}}
+🏷️ {price}: ${{price}}
+📊 {mcap}: ${{mcap}}
+
+{links}",
+        sold = label("alert-sold"),
+        value = label("alert-value"),
+        price = label("alert-price"),
+        mcap = label("alert-marketcap"),
+        links = link_line(language),
+    )
+}
+
+fn link_line(language: Language) -> String {
+    format!(
+        "<a href=\"{{tx_link}}\">TX</a> | \
+<a href=\"{{chart_link}}\">{chart}</a> | \
+<a href=\"{{tg_link}}\">TG</a> | \
+<a href=\"{{twitter_link}}\">X</a> | \
+<a href=\"{{website_link}}\">{website}</a>",
+        chart = html::escape(&language.text("alert-chart")),
+        website = html::escape(&language.text("alert-website")),
+    )
+}
+
+/// Telegram's caption limit, as alerts may be posted with a photo or video.
+pub const MAX_TEMPLATE_CHARS: usize = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placeholder {
+    EmojiBar,
+    SpentUsd,
+    SpentNative,
+    GotAmount,
+    Amount,
+    ValueUsd,
+    Symbol,
+    Gas,
+    Price,
+    Mcap,
+    Buyer,
+    TxLink,
+    ChartLink,
+    DexLink,
+    DexName,
+    TrendingLink,
+    AdsLink,
+    TgLink,
+    TwitterLink,
+    WebsiteLink,
+}
+
+impl Placeholder {
+    pub const ALL: [Placeholder; 20] = [
+        Self::EmojiBar,
+        Self::SpentUsd,
+        Self::SpentNative,
+        Self::GotAmount,
+        Self::Amount,
+        Self::ValueUsd,
+        Self::Symbol,
+        Self::Gas,
+        Self::Price,
+        Self::Mcap,
+        Self::Buyer,
+        Self::TxLink,
+        Self::ChartLink,
+        Self::DexLink,
+        Self::DexName,
+        Self::TrendingLink,
+        Self::AdsLink,
+        Self::TgLink,
+        Self::TwitterLink,
+        Self::WebsiteLink,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::EmojiBar => "emoji_bar",
+            Self::SpentUsd => "spent_usd",
+            Self::SpentNative => "spent_native",
+            Self::GotAmount => "got_amount",
+            Self::Amount => "amount",
+            Self::ValueUsd => "value_usd",
+            Self::Symbol => "symbol",
+            Self::Gas => "gas",
+            Self::Price => "price",
+            Self::Mcap => "mcap",
+            Self::Buyer => "buyer",
+            Self::TxLink => "tx_link",
+            Self::ChartLink => "chart_link",
+            Self::DexLink => "dex_link",
+            Self::DexName => "dex_name",
+            Self::TrendingLink => "trending_link",
+            Self::AdsLink => "ads_link",
+            Self::TgLink => "tg_link",
+            Self::TwitterLink => "twitter_link",
+            Self::WebsiteLink => "website_link",
+        }
+    }
+
+    pub fn description(self, language: Language) -> String {
+        language.text(&format!("placeholder-{}", self.name().replace('_', "-")))
+    }
+
+    /// Whether the value is a URL, which may be used as a link's `href`.
+    pub fn is_link(self) -> bool {
+        matches!(
+            self,
+            Self::TxLink
+                | Self::ChartLink
+                | Self::DexLink
+                | Self::TrendingLink
+                | Self::AdsLink
+                | Self::TgLink
+                | Self::TwitterLink
+                | Self::WebsiteLink
+        )
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|placeholder| placeholder.name() == name)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TemplateError {
+    Empty,
+    TooLong(usize),
+    UnknownPlaceholder(String),
+    UnclosedPlaceholder,
+    StrayClosingBrace,
+    /// A placeholder that is not a URL used as a link's `href`.
+    NotALink(&'static str),
+    /// A link placeholder with more than the placeholder in the `href`.
+    WholeLink(&'static str),
+    UnclosedLink,
+    Markup(MarkupError),
+}
+
+impl TemplateError {
+    pub fn localized(&self, language: Language) -> String {
+        let braced = |name: &str| format!("{{{}}}", name);
+        match self {
+            Self::Empty => language.text("template-error-empty"),
+            Self::TooLong(chars) => language.text_with(
+                "template-error-too-long",
+                &[
+                    ("chars", &chars.to_string()),
+                    ("max", &MAX_TEMPLATE_CHARS.to_string()),
+                ],
+            ),
+            Self::UnknownPlaceholder(name) => language.text_with(
+                "template-error-unknown-placeholder",
+                &[("placeholder", &braced(name))],
+            ),
+            Self::UnclosedPlaceholder => language.text("template-error-unclosed-placeholder"),
+            Self::StrayClosingBrace => language.text("template-error-stray-closing-brace"),
+            Self::NotALink(name) => language.text_with(
+                "template-error-not-a-link",
+                &[("placeholder", &braced(name))],
+            ),
+            Self::WholeLink(name) => language.text_with(
+                "template-error-whole-link",
+                &[("placeholder", &braced(name))],
+            ),
+            Self::UnclosedLink => language.text("template-error-unclosed-link"),
+            Self::Markup(error) => error.localized(language),
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localized(Language::En))
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+const LINK_OPEN: &str = "<a href=\"";
+const LINK_OPEN_END: &str = "\">";
+const LINK_CLOSE: &str = "</a>";
+
+/// Separators removed together with a left out link.
+const LINK_SEPARATORS: &[&str] = &[" | ", " - "];
+
+#[derive(Debug, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Placeholder(Placeholder),
+    /// `<a href="{href}">label</a>`
+    Link {
+        href: Placeholder,
+        label: Vec<Part>,
+    },
+}
+
+/// A rendered piece of a line.
+enum Piece {
+    Text(String),
+    LeftOutLink,
+}
+
+/// A parsed, valid template.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        if source.trim().is_empty() {
+            return Err(TemplateError::Empty);
+        }
+        let chars = source.chars().count();
+        if chars > MAX_TEMPLATE_CHARS {
+            return Err(TemplateError::TooLong(chars));
+        }
+
+        let template = Self {
+            parts: group_links(tokenize(source)?)?,
+        };
+        // Placeholder values are escaped, only the admin's markup is checked
+        let stubbed = template.render(|placeholder| {
+            Some(if placeholder.is_link() {
+                "https://example.com".to_string()
+            } else {
+                "x".to_string()
+            })
+        });
+        html::check_markup(&stubbed).map_err(TemplateError::Markup)?;
+        Ok(template)
+    }
+
+    pub fn default_buy(language: Language) -> Self {
+        Self::parse(&default_buy_template(language)).expect("the default template is valid")
+    }
+
+    pub fn default_sell(language: Language) -> Self {
+        Self::parse(&default_sell_template(language)).expect("the default template is valid")
+    }
+
+    /// Fills in the placeholders with their escaped values. Lines with a
+    /// placeholder `value` returns `None` for are left out, and so are links
+    /// without a valid URL.
+    pub fn render(&self, value: impl Fn(Placeholder) -> Option<String>) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let mut line: Vec<Piece> = Vec::new();
+        let mut keep_line = true;
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => {
+                    let mut text_lines = text.split('\n');
+                    line.push(Piece::Text(
+                        text_lines.next().unwrap_or_default().to_string(),
+                    ));
+                    for next_line in text_lines {
+                        if keep_line {
+                            lines.push(join_line(std::mem::take(&mut line)));
+                        }
+                        line.clear();
+                        keep_line = true;
+                        line.push(Piece::Text(next_line.to_string()));
+                    }
+                }
+                Part::Placeholder(placeholder) => match value(*placeholder) {
+                    Some(value) => line.push(Piece::Text(html::escape(&value))),
+                    None => keep_line = false,
+                },
+                Part::Link { href, label } => {
+                    match value(*href).as_deref().and_then(html::safe_href) {
+                        Some(href) => line.push(Piece::Text(format!(
+                            "{}{}{}{}{}",
+                            LINK_OPEN,
+                            href,
+                            LINK_OPEN_END,
+                            render_inline(label, &value),
+                            LINK_CLOSE
+                        ))),
+                        None => line.push(Piece::LeftOutLink),
+                    }
+                }
+            }
+        }
+        if keep_line {
+            lines.push(join_line(line));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Splits the source into text and placeholders.
+fn tokenize(source: &str) -> Result<VecDeque<Part>, TemplateError> {
+    let mut parts = VecDeque::new();
+    let mut text = String::new();
+    let mut rest = source.chars().peekable();
+    while let Some(c) = rest.next() {
+        match c {
+            '{' if rest.peek() == Some(&'{') => {
+                rest.next();
+                text.push('{');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match rest.next() {
+                        Some('}') => break,
+                        Some('{') | None => return Err(TemplateError::UnclosedPlaceholder),
+                        Some(c) => name.push(c),
+                    }
+                }
+                let placeholder = Placeholder::from_name(name.trim())
+                    .ok_or(TemplateError::UnknownPlaceholder(name))?;
+                if !text.is_empty() {
+                    parts.push_back(Part::Text(std::mem::take(&mut text)));
+                }
+                parts.push_back(Part::Placeholder(placeholder));
+            }
+            '}' if rest.peek() == Some(&'}') => {
+                rest.next();
+                text.push('}');
+            }
+            '}' => return Err(TemplateError::StrayClosingBrace),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        parts.push_back(Part::Text(text));
+    }
+    Ok(parts)
+}
+
+/// Turns `<a href="` + placeholder + `">label</a>` into `Part::Link`.
+fn group_links(mut tokens: VecDeque<Part>) -> Result<Vec<Part>, TemplateError> {
+    let mut parts = Vec::new();
+    while let Some(token) = tokens.pop_front() {
+        let Part::Text(text) = token else {
+            parts.push(token);
+            continue;
+        };
+        let Some(before) = text.strip_suffix(LINK_OPEN) else {
+            parts.push(Part::Text(text));
+            continue;
+        };
+        let Some(Part::Placeholder(href)) = tokens.front() else {
+            parts.push(Part::Text(text));
+            continue;
+        };
+        let href = *href;
+        if !href.is_link() {
+            return Err(TemplateError::NotALink(href.name()));
+        }
+        tokens.pop_front();
+        let label_start = match tokens.pop_front() {
+            Some(Part::Text(after)) if after.starts_with(LINK_OPEN_END) => {
+                after[LINK_OPEN_END.len()..].to_string()
+            }
+            _ => return Err(TemplateError::WholeLink(href.name())),
+        };
+        if !before.is_empty() {
+            parts.push(Part::Text(before.to_string()));
+        }
+
+        let mut label = Vec::new();
+        tokens.push_front(Part::Text(label_start));
+        loop {
+            match tokens.pop_front() {
+                Some(Part::Text(text)) => match text.split_once(LINK_CLOSE) {
+                    Some((end, after)) => {
+                        if !end.is_empty() {
+                            label.push(Part::Text(end.to_string()));
+                        }
+                        if !after.is_empty() {
+                            tokens.push_front(Part::Text(after.to_string()));
+                        }
+                        break;
+                    }
+                    None => label.push(Part::Text(text)),
+                },
+                Some(part) => label.push(part),
+                None => return Err(TemplateError::UnclosedLink),
+            }
+        }
+        parts.push(Part::Link { href, label });
+    }
+    Ok(parts)
+}
+
+/// A link label, where a placeholder without a value is just empty.
+fn render_inline(parts: &[Part], value: &impl Fn(Placeholder) -> Option<String>) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            Part::Text(text) => text.clone(),
+            Part::Placeholder(placeholder)
+            | Part::Link {
+                href: placeholder, ..
+            } => value(*placeholder).map_or(String::new(), |value| html::escape(&value)),
+        })
+        .collect()
+}
+
+/// Joins a line, dropping left out links with one of their separators.
+fn join_line(pieces: Vec<Piece>) -> String {
+    let mut texts: Vec<String> = Vec::new();
+    let mut strip_next_separator = false;
+    for piece in pieces {
+        match piece {
+            Piece::Text(mut text) => {
+                if strip_next_separator {
+                    if let Some(separator) = LINK_SEPARATORS
+                        .iter()
+                        .find(|separator| text.starts_with(**separator))
+                    {
+                        text.drain(..separator.len());
+                    }
+                    strip_next_separator = false;
+                }
+                texts.push(text);
+            }
+            Piece::LeftOutLink => {
+                // `A | B` without B: strip the separator before it, without
+                // A: the one after it
+                let before = texts.iter_mut().rev().find(|text| !text.is_empty());
+                match before.and_then(|text| {
+                    LINK_SEPARATORS
+                        .iter()
+                        .find(|separator| text.ends_with(**separator))
+                        .map(|separator| (text, separator.len()))
+                }) {
+                    Some((text, len)) => text.truncate(text.len() - len),
+                    None => strip_next_separator = true,
+                }
+            }
+        }
+    }
+    texts.concat()
+}
+
+impl std::str::FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
+
+/// The placeholder list shown to admins editing a template.
+pub fn placeholder_help(language: Language) -> String {
+    Placeholder::ALL
+        .into_iter()
+        .map(|placeholder| {
+            format!(
+                "{{{}}}: {}",
+                placeholder.name(),
+                placeholder.description(language)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(placeholder: Placeholder) -> Option<String> {
+        match placeholder {
+            Placeholder::Gas => None,
+            other => Some(other.name().to_uppercase()),
+        }
+    }
+
+    #[test]
+    fn fills_named_placeholders() {
+        let template =
+            Template::parse("{emoji_bar}\nGot {got_amount} ${symbol} {{literal}}").unwrap();
+        assert_eq!(
+            template.render(values),
+            "EMOJI_BAR\nGot GOT_AMOUNT $SYMBOL {literal}"
+        );
+    }
+
+    #[test]
+    fn leaves_out_lines_without_a_value() {
+        let template = Template::parse("a\n⛽ Gas: {gas}\nb {price}").unwrap();
+        assert_eq!(template.render(values), "a\nb PRICE");
+
+        let template = Template::parse("a\n⛽ Gas: {gas}").unwrap();
+        assert_eq!(template.render(values), "a");
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        assert_eq!(Template::parse(" \n"), Err(TemplateError::Empty));
+        assert_eq!(
+            Template::parse("{volume}"),
+            Err(TemplateError::UnknownPlaceholder("volume".to_string()))
+        );
+        assert_eq!(
+            Template::parse("Got {got_amount"),
+            Err(TemplateError::UnclosedPlaceholder)
+        );
+        assert_eq!(
+            Template::parse("Got {got_{amount}}"),
+            Err(TemplateError::UnclosedPlaceholder)
+        );
+        assert_eq!(
+            Template::parse("Got }"),
+            Err(TemplateError::StrayClosingBrace)
+        );
+        assert_eq!(
+            Template::parse(&"x".repeat(MAX_TEMPLATE_CHARS + 1)),
+            Err(TemplateError::TooLong(MAX_TEMPLATE_CHARS + 1))
+        );
+    }
+
+    #[test]
+    fn escapes_values_and_leaves_out_empty_links() {
+        let template = Template::parse(
+            "{symbol}\n<a href=\"{tx_link}\">TX</a> | <a href=\"{tg_link}\">TG</a> | \
+             <a href=\"{twitter_link}\">X</a>",
+        )
+        .unwrap();
+        let value = |placeholder| match placeholder {
+            Placeholder::Symbol => Some("<b>&</b>".to_string()),
+            Placeholder::TxLink => Some("https://apescan.io/tx/0x1".to_string()),
+            Placeholder::TwitterLink => Some("https://x.com/a?b=\"c\"".to_string()),
+            _ => Some(String::new()),
+        };
+        assert_eq!(
+            template.render(value),
+            "&lt;b&gt;&amp;&lt;/b&gt;\n\
+             <a href=\"https://apescan.io/tx/0x1\">TX</a> | \
+             <a href=\"https://x.com/a?b=&quot;c&quot;\">X</a>"
+        );
+
+        // Leading and trailing links take their separator with them
+        let value = |placeholder| match placeholder {
+            Placeholder::TgLink => Some("https://t.me/gorilla".to_string()),
+            _ => Some("not a link".to_string()),
+        };
+        assert_eq!(
+            template.render(value),
+            "not a link\n<a href=\"https://t.me/gorilla\">TG</a>"
+        );
+    }
+
+    #[test]
+    fn leaves_out_the_ad_links_of_chains_without_them() {
+        let template = Template::default_buy(Language::En);
+        let links = |trending: &str, ads: &str| {
+            let (trending, ads) = (trending.to_string(), ads.to_string());
+            move |placeholder| match placeholder {
+                Placeholder::DexLink => Some("https://dex.example".to_string()),
+                Placeholder::TrendingLink => Some(trending.clone()),
+                Placeholder::AdsLink => Some(ads.clone()),
+                other => values(other),
+            }
+        };
+        let dex = "✅ Dex: <a href=\"https://dex.example\">DEX_NAME</a>";
+
+        let rendered = template.render(links("https://t.me/trending", "https://t.me/ads"));
+        assert!(
+            rendered.contains(&format!(
+                "{} | <a href=\"https://t.me/trending\">🔖 Book Trending</a> - \
+                 <a href=\"https://t.me/ads\">ADS</a>\n",
+                dex
+            )),
+            "{}",
+            rendered
+        );
+        let rendered = template.render(links("https://t.me/trending", ""));
+        assert!(
+            rendered.contains(&format!(
+                "{} | <a href=\"https://t.me/trending\">🔖 Book Trending</a>\n",
+                dex
+            )),
+            "{}",
+            rendered
+        );
+        let rendered = template.render(links("", ""));
+        assert!(rendered.contains(&format!("{}\n", dex)), "{}", rendered);
+    }
+
+    #[test]
+    fn rejects_invalid_markup() {
+        assert_eq!(
+            Template::parse("<a href=\"{symbol}\">x</a>"),
+            Err(TemplateError::NotALink("symbol"))
+        );
+        assert_eq!(
+            Template::parse("<a href=\"{tg_link}/x\">TG</a>"),
+            Err(TemplateError::WholeLink("tg_link"))
+        );
+        assert!(matches!(
+            Template::parse("<b>{symbol}"),
+            Err(TemplateError::Markup(_))
+        ));
+        assert!(matches!(
+            Template::parse("Buy & hold {symbol}"),
+            Err(TemplateError::Markup(_))
+        ));
+        assert!(matches!(
+            Template::parse("<a href=\"javascript:x\">x</a>"),
+            Err(TemplateError::Markup(_))
+        ));
+        assert!(Template::parse("<b>Buy &amp; hold</b> <i>{symbol}</i>").is_ok());
+    }
+
+    #[test]
+    fn default_templates_and_help_cover_every_placeholder() {
+        for language in Language::ALL {
+            let help = placeholder_help(language);
+            let buy = default_buy_template(language);
+            let sell = default_sell_template(language);
+            for placeholder in Placeholder::ALL {
+                let name = format!("{{{}}}", placeholder.name());
+                assert!(help.contains(&name));
+                assert!(
+                    buy.contains(&name)
+                        || sell.contains(&name)
+                        || placeholder == Placeholder::Buyer,
+                    "{}",
+                    name
+                );
+            }
+            assert!(!Template::default_buy(language).render(values).contains('{'));
+            assert!(!Template::default_sell(language)
+                .render(values)
+                .contains('{'));
+        }
+    }
+
+    #[test]
+    fn errors_are_localized() {
+        let error = Template::parse("{volume}").unwrap_err();
+        assert_eq!(error.to_string(), "unknown placeholder {volume}");
+        assert_eq!(
+            error.localized(Language::De),
+            "unbekannter Platzhalter {volume}"
+        );
+        assert!(default_buy_template(Language::De).contains("💰 Erhalten: {got_amount}"));
+    }
+}
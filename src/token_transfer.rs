@@ -1,13 +1,23 @@
 use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use crate::amount::{AmountError, TokenAmount};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct TokenTransfer {
     pub items: Vec<TokenTransferItem>,
+    /// Query parameters for the next (older) page, `None` on the last page.
+    #[serde(default)]
+    pub next_page_params: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct TokenTransferItem {
     pub block_hash: String,
+    /// Left out by older Blockscout versions, which cannot be tracked by
+    /// cursor.
+    #[serde(default)]
+    pub block_number: Option<u64>,
     pub from: AddressInfo,
     pub to: AddressInfo,
     pub token: TokenInfo,
@@ -57,6 +67,7 @@ impl From<Vec<(String, AddressInfo, AddressInfo, TokenInfo, Total, String, Strin
             .into_iter()
             .map(|item| TokenTransferItem {
                 block_hash: item.0,
+                block_number: None,
                 from: item.1,
                 to: item.2,
                 token: item.3,
@@ -68,7 +79,65 @@ impl From<Vec<(String, AddressInfo, AddressInfo, TokenInfo, Total, String, Strin
                 r#type: item.9,
             })
             .collect();
-        TokenTransfer { items }
+        TokenTransfer {
+            items,
+            next_page_params: None,
+        }
+    }
+}
+
+/// Position of a transfer in the chain, ordered chronologically.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TransferCursor {
+    pub block_number: u64,
+    pub log_index: u64,
+}
+
+impl TransferCursor {
+    /// Before every transfer.
+    pub const START: TransferCursor = TransferCursor {
+        block_number: 0,
+        log_index: 0,
+    };
+}
+
+/// A transfer without a readable position in the chain. Ordering it anywhere
+/// would drop or repeat trades, so the explorer's answer is not usable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorError(String);
+
+impl fmt::Display for CursorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CursorError {}
+
+impl TokenTransferItem {
+    pub fn cursor(&self) -> Result<TransferCursor, CursorError> {
+        let block_number = self.block_number.ok_or_else(|| {
+            CursorError(format!("transfer in {} has no block number", self.tx_hash))
+        })?;
+        let log_index = self.log_index.trim().parse().map_err(|_| {
+            CursorError(format!(
+                "transfer in {} has an invalid log index {:?}",
+                self.tx_hash, self.log_index
+            ))
+        })?;
+        Ok(TransferCursor {
+            block_number,
+            log_index,
+        })
+    }
+
+    /// The amount transferred.
+    pub fn amount(&self) -> Result<TokenAmount, AmountError> {
+        TokenAmount::parse(&self.total.value, &self.total.decimals)
+    }
+
+    pub fn total_supply(&self) -> Result<TokenAmount, AmountError> {
+        TokenAmount::parse(&self.token.total_supply, &self.token.decimals)
     }
 }
 
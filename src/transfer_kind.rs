@@ -0,0 +1,137 @@
+use std::collections::HashSet;
+
+use crate::token_transfer::TokenTransferItem;
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+const DEAD_ADDRESS: &str = "0x000000000000000000000000000000000000dead";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferKind {
+    Buy,
+    Sell,
+    AddLiquidity,
+    RemoveLiquidity,
+    Burn,
+    Transfer,
+}
+
+/// Labels a transfer of the tracked token from its `from` and `to` addresses.
+///
+/// `pairs` holds the lowercased DEX pair or pool addresses of the token. Tokens
+/// leaving a pool are bought, tokens entering one are sold, unless the called
+/// method says liquidity is being moved.
+pub fn classify(item: &TokenTransferItem, pairs: &HashSet<String>) -> TransferKind {
+    let from = item.from.hash.to_lowercase();
+    let to = item.to.hash.to_lowercase();
+    let method = item.method.to_lowercase();
+
+    if to == ZERO_ADDRESS || to == DEAD_ADDRESS || item.r#type == "token_burning" {
+        return TransferKind::Burn;
+    }
+
+    match (pairs.contains(&from), pairs.contains(&to)) {
+        (true, false) if method.contains("removeliquidity") => TransferKind::RemoveLiquidity,
+        (true, false) => TransferKind::Buy,
+        (false, true) if method.contains("addliquidity") => TransferKind::AddLiquidity,
+        (false, true) => TransferKind::Sell,
+        _ => TransferKind::Transfer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token_transfer::AddressInfo;
+
+    const PAIR: &str = "0x3b1e2c9d8f7a6b5c4d3e2f1a0b9c8d7e6f5a4b3c";
+    const OTHER_PAIR: &str = "0x9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b";
+    const WALLET: &str = "0xb0b0000000000000000000000000000000000001";
+    const OTHER_WALLET: &str = "0xb0b0000000000000000000000000000000000002";
+
+    fn transfer(from: &str, to: &str, method: &str) -> TokenTransferItem {
+        let address = |hash: &str| AddressInfo {
+            hash: hash.to_string(),
+            ..AddressInfo::default()
+        };
+        TokenTransferItem {
+            from: address(from),
+            to: address(to),
+            method: method.to_string(),
+            r#type: "token_transfer".to_string(),
+            ..TokenTransferItem::default()
+        }
+    }
+
+    fn pairs() -> HashSet<String> {
+        HashSet::from([PAIR.to_string(), OTHER_PAIR.to_string()])
+    }
+
+    #[test]
+    fn tokens_leaving_a_pair_are_bought_and_entering_one_sold() {
+        assert_eq!(
+            classify(&transfer(PAIR, WALLET, "swap"), &pairs()),
+            TransferKind::Buy
+        );
+        // Explorers return checksummed addresses
+        let checksummed = PAIR.to_uppercase().replacen("0X", "0x", 1);
+        assert_eq!(
+            classify(&transfer(&checksummed, WALLET, "swap"), &pairs()),
+            TransferKind::Buy
+        );
+        assert_eq!(
+            classify(&transfer(WALLET, PAIR, "swapExactTokensForETH"), &pairs()),
+            TransferKind::Sell
+        );
+    }
+
+    #[test]
+    fn liquidity_is_told_apart_by_the_method() {
+        assert_eq!(
+            classify(&transfer(WALLET, PAIR, "addLiquidityETH"), &pairs()),
+            TransferKind::AddLiquidity
+        );
+        assert_eq!(
+            classify(
+                &transfer(PAIR, WALLET, "removeLiquidityWithPermit"),
+                &pairs()
+            ),
+            TransferKind::RemoveLiquidity
+        );
+    }
+
+    #[test]
+    fn burns_win_over_everything_else() {
+        assert_eq!(
+            classify(&transfer(WALLET, ZERO_ADDRESS, "transfer"), &pairs()),
+            TransferKind::Burn
+        );
+        let dead = DEAD_ADDRESS.replacen("dead", "dEaD", 1);
+        assert_eq!(
+            classify(&transfer(PAIR, &dead, "swap"), &pairs()),
+            TransferKind::Burn
+        );
+        let burning = TokenTransferItem {
+            r#type: "token_burning".to_string(),
+            ..transfer(WALLET, OTHER_WALLET, "burn")
+        };
+        assert_eq!(classify(&burning, &pairs()), TransferKind::Burn);
+    }
+
+    #[test]
+    fn everything_else_is_a_plain_transfer() {
+        assert_eq!(
+            classify(&transfer(WALLET, OTHER_WALLET, "transfer"), &pairs()),
+            TransferKind::Transfer
+        );
+        // Routed from one pool into another
+        assert_eq!(
+            classify(&transfer(PAIR, OTHER_PAIR, "swap"), &pairs()),
+            TransferKind::Transfer
+        );
+        // Without known pairs nothing is a trade
+        assert_eq!(
+            classify(&transfer(PAIR, WALLET, "swap"), &HashSet::new()),
+            TransferKind::Transfer
+        );
+    }
+}
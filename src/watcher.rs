@@ -0,0 +1,724 @@
+use log::{error, warn};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, InputFile};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::amount::{AmountError, TokenAmount};
+use crate::chain::{find_chain, Chain};
+use crate::dex_pairs::{DexPairs, PairSource};
+use crate::explorer::{BlockExplorer, ExplorerResult};
+use crate::format;
+use crate::i18n::Language;
+use crate::price::{PriceProvider, SwapPricing};
+use crate::regex::is_token_address;
+use crate::setting_opts::SettingOpts;
+use crate::swap::{native_amount, swap_price, NativeUsdCache};
+use crate::template::{Placeholder, Template};
+use crate::token_transfer::{TokenTransferItem, TransferCursor};
+use crate::transfer_kind::{classify, TransferKind};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WatcherKey {
+    pub group_chat_id: String,
+    pub chain_id: String,
+    pub token_address: String,
+}
+
+impl WatcherKey {
+    pub fn new(group_chat_id: &str, chain_id: &str, token_address: &str) -> Self {
+        Self {
+            group_chat_id: group_chat_id.to_string(),
+            chain_id: chain_id.to_string(),
+            token_address: token_address.to_lowercase(),
+        }
+    }
+
+    fn poller_key(&self) -> PollerKey {
+        (self.chain_id.clone(), self.token_address.clone())
+    }
+}
+
+/// A buy or sell seen by a token poller. It is shared by every subscribed
+/// group, each of which applies its own `SettingOpts` before posting it.
+#[derive(Clone, Debug)]
+pub struct TradeEvent {
+    pub kind: TransferKind,
+    pub chain: &'static Chain,
+    pub token_address: String,
+    pub token_symbol: String,
+    pub tx_hash: String,
+    /// The buyer of a buy or the seller of a sell.
+    pub trader: String,
+    pub token_price: f64,
+    pub token_amount: TokenAmount,
+    /// Native coin paid for a buy or received for a sell, if the swap has a
+    /// native side.
+    pub native_amount: Option<TokenAmount>,
+    /// Gas paid by the buyer, in the native coin. Only known for buys.
+    pub gas_fee: Option<TokenAmount>,
+    pub total_supply: TokenAmount,
+}
+
+type Subscribers = Arc<RwLock<HashMap<String, SettingOpts>>>;
+
+/// Chain id and lowercased token address.
+type PollerKey = (String, String);
+
+struct TokenPoller {
+    subscribers: Subscribers,
+    handle: JoinHandle<()>,
+}
+
+/// Every running buy watcher, keyed by group, chain and token.
+///
+/// Starting, stopping and restarting watchers must go through the registry so
+/// that a group never ends up with two loops posting the same buys, and so
+/// that deleting a token actually stops its loop. Groups tracking the same
+/// token on the same chain share a single upstream poller.
+pub struct WatcherRegistry {
+    bot: Bot,
+    prices: Arc<dyn PriceProvider>,
+    swap_pricing: SwapPricing,
+    native_usd: Arc<NativeUsdCache>,
+    explorers: ExplorerFor,
+    pair_source: Arc<dyn PairSource>,
+    pollers: RwLock<HashMap<PollerKey, TokenPoller>>,
+}
+
+/// Opens the block explorer a chain's pollers read from.
+pub type ExplorerFor = Box<dyn Fn(&'static Chain) -> Arc<dyn BlockExplorer> + Send + Sync>;
+
+impl WatcherRegistry {
+    pub fn new(
+        bot: Bot,
+        prices: Arc<dyn PriceProvider>,
+        swap_pricing: SwapPricing,
+        explorers: ExplorerFor,
+        pair_source: Arc<dyn PairSource>,
+    ) -> Self {
+        Self {
+            bot,
+            prices,
+            swap_pricing,
+            native_usd: Arc::new(NativeUsdCache::new()),
+            explorers,
+            pair_source,
+            pollers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Subscribes the group to buys of the token, starting the token's poller
+    /// if needed. Returns `true` if the group was not subscribed yet.
+    pub async fn start(
+        &self,
+        setting_opts: SettingOpts,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let key = watcher_key(&setting_opts);
+        key.group_chat_id.parse::<i64>()?;
+        if !is_token_address(&setting_opts.token_address) {
+            return Err(format!("invalid token address {:?}", setting_opts.token_address).into());
+        }
+        let chain =
+            find_chain(&key.chain_id).ok_or_else(|| format!("unknown chain {:?}", key.chain_id))?;
+
+        let poller_key = key.poller_key();
+        let mut pollers = self.pollers.write().await;
+        if let Some(poller) = pollers
+            .get(&poller_key)
+            .filter(|poller| !poller.handle.is_finished())
+        {
+            let mut subscribers = poller.subscribers.write().await;
+            if subscribers.contains_key(&key.group_chat_id) {
+                return Ok(false);
+            }
+            subscribers.insert(key.group_chat_id, setting_opts);
+            return Ok(true);
+        }
+
+        // A poller that ended, e.g. after a panic, hands its groups on to the
+        // new one
+        let mut subscribed = match pollers.get(&poller_key) {
+            Some(finished) => finished.subscribers.read().await.clone(),
+            None => HashMap::new(),
+        };
+        let was_subscribed = subscribed.insert(key.group_chat_id, setting_opts).is_some();
+        let subscribers = Arc::new(RwLock::new(subscribed));
+        let handle =
+            spawn_token_poller(self.token_poll(chain, &key.token_address, subscribers.clone()));
+        pollers.insert(
+            poller_key,
+            TokenPoller {
+                subscribers,
+                handle,
+            },
+        );
+        Ok(!was_subscribed)
+    }
+
+    /// Unsubscribes the group from the token and stops the token's poller once
+    /// nobody listens to it anymore. Returns `true` if the group was
+    /// subscribed.
+    pub async fn stop(&self, group_chat_id: &str, chain_id: &str, token_address: &str) -> bool {
+        let key = WatcherKey::new(group_chat_id, chain_id, token_address);
+        let poller_key = key.poller_key();
+        let mut pollers = self.pollers.write().await;
+        let Some(poller) = pollers.get(&poller_key) else {
+            return false;
+        };
+
+        let mut subscribers = poller.subscribers.write().await;
+        let was_subscribed = subscribers.remove(&key.group_chat_id).is_some();
+        let is_idle = subscribers.is_empty();
+        drop(subscribers);
+
+        if is_idle {
+            if let Some(poller) = pollers.remove(&poller_key) {
+                poller.handle.abort();
+            }
+        }
+        was_subscribed
+    }
+
+    /// Replaces the settings the group uses for the token, subscribing it if
+    /// it was not yet.
+    pub async fn restart(
+        &self,
+        setting_opts: SettingOpts,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let key = watcher_key(&setting_opts);
+        if let Some(poller) = self
+            .pollers
+            .read()
+            .await
+            .get(&key.poller_key())
+            .filter(|poller| !poller.handle.is_finished())
+        {
+            let mut subscribers = poller.subscribers.write().await;
+            if let Some(subscribed) = subscribers.get_mut(&key.group_chat_id) {
+                *subscribed = setting_opts;
+                return Ok(());
+            }
+        }
+        self.start(setting_opts).await?;
+        Ok(())
+    }
+
+    fn token_poll(
+        &self,
+        chain: &'static Chain,
+        token_address: &str,
+        subscribers: Subscribers,
+    ) -> TokenPoll {
+        TokenPoll {
+            bot: self.bot.clone(),
+            prices: self.prices.clone(),
+            swap_pricing: self.swap_pricing,
+            native_usd: self.native_usd.clone(),
+            explorer: (self.explorers)(chain),
+            pair_source: self.pair_source.clone(),
+            chain,
+            token_adr: token_address.to_string(),
+            subscribers,
+            cursor: None,
+            failed_attempts: 0,
+            pairs: HashSet::new(),
+            pairs_refreshed_at: None,
+        }
+    }
+
+    /// Whether a DEX pair of the token is listed on its chain. Buys and sells
+    /// are told apart by their pair, so without one nothing is alerted.
+    /// `false` as well when the pairs cannot be looked up.
+    pub async fn has_dex_pairs(&self, chain_id: &str, token_address: &str) -> bool {
+        let Some(chain) = find_chain(chain_id) else {
+            return false;
+        };
+        match self.pair_source.dex_pairs(token_address).await {
+            Ok(dex_pairs) => !pair_addresses(chain, &dex_pairs).is_empty(),
+            Err(e) => {
+                error!("Error fetching DEX pairs: {}", e);
+                false
+            }
+        }
+    }
+
+    pub async fn list(&self) -> Vec<WatcherKey> {
+        let mut keys = Vec::new();
+        for ((chain_id, token_address), poller) in self.pollers.read().await.iter() {
+            if poller.handle.is_finished() {
+                continue;
+            }
+            for group_chat_id in poller.subscribers.read().await.keys() {
+                keys.push(WatcherKey::new(group_chat_id, chain_id, token_address));
+            }
+        }
+        keys
+    }
+}
+
+fn watcher_key(setting_opts: &SettingOpts) -> WatcherKey {
+    WatcherKey::new(
+        &setting_opts.group_chat_id,
+        &setting_opts.chain_id,
+        &setting_opts.token_address,
+    )
+}
+
+/// Upper bound on the pages followed in one poll, so a long outage cannot make
+/// a single poll walk the whole transfer history.
+const MAX_PAGES_PER_POLL: usize = 10;
+
+/// How many polls in a row a trade that cannot be read or priced is tried
+/// before it is skipped, so one broken transaction cannot stall the token.
+const MAX_TRADE_ATTEMPTS: u32 = 3;
+
+/// How long the DEX pairs of a token are trusted before they are looked up
+/// again, so newly created pools are picked up.
+const PAIR_REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// How soon a token without any DEX pair is looked up again, so its alerts
+/// start shortly after its first pool is listed.
+const PAIR_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The upstream poller of one token and how far it has read.
+struct TokenPoll {
+    bot: Bot,
+    prices: Arc<dyn PriceProvider>,
+    swap_pricing: SwapPricing,
+    native_usd: Arc<NativeUsdCache>,
+    explorer: Arc<dyn BlockExplorer>,
+    pair_source: Arc<dyn PairSource>,
+    chain: &'static Chain,
+    token_adr: String,
+    subscribers: Subscribers,
+    /// The last transfer handled, `None` until the first fetch succeeds.
+    cursor: Option<TransferCursor>,
+    failed_attempts: u32,
+    pairs: HashSet<String>,
+    pairs_refreshed_at: Option<Instant>,
+}
+
+fn spawn_token_poller(mut poll: TokenPoll) -> JoinHandle<()> {
+    let interval = tokio::time::interval(std::time::Duration::from_secs(5));
+    tokio::spawn(async move {
+        let mut interval = interval;
+        loop {
+            interval.tick().await;
+            poll.poll().await;
+        }
+    })
+}
+
+impl TokenPoll {
+    /// One round: refreshes the pairs when due, then posts every trade that
+    /// landed since the last round.
+    async fn poll(&mut self) {
+        if self.pairs_due() {
+            self.refresh_pairs().await;
+        }
+        let new_transfers =
+            match fetch_new_transfers(self.explorer.as_ref(), &self.token_adr, self.cursor).await {
+                Ok(new_transfers) => new_transfers,
+                Err(e) => {
+                    error!("Error fetching token transfers: {}", e);
+                    return;
+                }
+            };
+        if self.cursor.is_none() {
+            // Only report buys that land after the watcher (re)started, so a
+            // restart never reposts. A token without transfers yet reports
+            // its very first one.
+            self.cursor = match new_transfers.last() {
+                Some(last_transfer) => last_transfer.cursor().ok(),
+                None => Some(TransferCursor::START),
+            };
+            return;
+        }
+
+        let mut api_price = None;
+        for transfer in &new_transfers {
+            // `fetch_new_transfers` only returns transfers with a cursor
+            let Ok(transfer_cursor) = transfer.cursor() else {
+                break;
+            };
+            if let Err(e) = self.handle_transfer(transfer, &mut api_price).await {
+                self.failed_attempts += 1;
+                if self.failed_attempts < MAX_TRADE_ATTEMPTS {
+                    // The cursor stays before the trade and the ones after
+                    // it, the next poll tries them again
+                    warn!(
+                        "Retrying trade {} on the next poll: {}",
+                        transfer.tx_hash, e
+                    );
+                    break;
+                }
+                error!(
+                    "Skipping trade {} after {} attempts: {}",
+                    transfer.tx_hash, self.failed_attempts, e
+                );
+            }
+            self.cursor = Some(transfer_cursor);
+            self.failed_attempts = 0;
+        }
+    }
+
+    fn pairs_due(&self) -> bool {
+        let interval = if self.pairs.is_empty() {
+            PAIR_RETRY_INTERVAL
+        } else {
+            PAIR_REFRESH_INTERVAL
+        };
+        self.pairs_refreshed_at
+            .is_none_or(|at| at.elapsed() >= interval)
+    }
+
+    async fn refresh_pairs(&mut self) {
+        match self.pair_source.dex_pairs(&self.token_adr).await {
+            Ok(dex_pairs) => {
+                self.pairs = pair_addresses(self.chain, &dex_pairs);
+                self.pairs_refreshed_at = Some(Instant::now());
+                if self.pairs.is_empty() {
+                    log::warn!(
+                        "No DEX pairs found for {} on {}",
+                        self.token_adr,
+                        self.chain.name
+                    );
+                }
+            }
+            Err(e) => error!("Error fetching DEX pairs: {}", e),
+        }
+    }
+
+    /// Posts the transfer to the subscribed groups if it is a trade.
+    /// `api_price` caches the providers' price for the rest of the poll.
+    async fn handle_transfer(
+        &self,
+        transfer: &TokenTransferItem,
+        api_price: &mut Option<Option<f64>>,
+    ) -> ExplorerResult<()> {
+        let kind = classify(transfer, &self.pairs);
+        if !matches!(kind, TransferKind::Buy | TransferKind::Sell) {
+            return Ok(());
+        }
+        let subscribed: Vec<SettingOpts> = self
+            .subscribers
+            .read()
+            .await
+            .values()
+            .filter(|setting_opts| kind == TransferKind::Buy || setting_opts.sell_alerts)
+            .cloned()
+            .collect();
+        if subscribed.is_empty() {
+            return Ok(());
+        }
+
+        let explorer = self.explorer.as_ref();
+        let mut event = trade_event(explorer, self.chain, transfer, kind, &self.pairs).await?;
+
+        //get token price from the providers, once per poll
+        let api_price = match *api_price {
+            Some(api_price) => api_price,
+            None if self.swap_pricing == SwapPricing::Only => *api_price.insert(None),
+            None => match self.prices.token_price(self.chain, &self.token_adr).await {
+                Ok(price) => *api_price.insert(Some(price.usd)),
+                Err(e) => {
+                    error!("Error fetching token price: {}", e);
+                    *api_price.insert(None)
+                }
+            },
+        };
+        event.token_price = match api_price {
+            Some(price) => price,
+            None if self.swap_pricing == SwapPricing::Off => {
+                return Err("no price provider has a price".into())
+            }
+            None => {
+                swap_price(
+                    explorer,
+                    self.chain,
+                    event.native_amount,
+                    event.token_amount,
+                    &self.native_usd,
+                )
+                .await?
+            }
+        };
+
+        for setting_opts in subscribed {
+            notify_trade(&self.bot, &event, &setting_opts).await;
+        }
+        Ok(())
+    }
+}
+
+/// Returns every transfer after `cursor`, oldest first, following the
+/// explorer's pagination when more than one page landed since the last poll.
+/// Without a cursor only the newest page is returned. A transfer without a
+/// readable cursor fails the whole fetch, so every returned one has one.
+async fn fetch_new_transfers(
+    explorer: &dyn BlockExplorer,
+    token_address: &str,
+    cursor: Option<TransferCursor>,
+) -> ExplorerResult<Vec<TokenTransferItem>> {
+    let mut new_transfers = Vec::new();
+    let mut page_params = None;
+    for _ in 0..MAX_PAGES_PER_POLL {
+        let page = explorer
+            .token_transfers(token_address, page_params.as_ref())
+            .await?;
+        let mut reached_cursor = false;
+        for item in page.items {
+            let item_cursor = item.cursor()?;
+            if cursor.is_some_and(|cursor| item_cursor <= cursor) {
+                reached_cursor = true;
+            } else {
+                new_transfers.push((item_cursor, item));
+            }
+        }
+        match page.next_page_params {
+            Some(next_page_params) if cursor.is_some() && !reached_cursor => {
+                page_params = Some(next_page_params)
+            }
+            _ => break,
+        }
+    }
+
+    new_transfers.sort_by_key(|(item_cursor, _)| *item_cursor);
+    new_transfers.dedup_by_key(|(item_cursor, _)| *item_cursor);
+    Ok(new_transfers.into_iter().map(|(_, item)| item).collect())
+}
+
+/// Lowercased addresses of the token's pairs on the chain.
+fn pair_addresses(chain: &Chain, dex_pairs: &DexPairs) -> HashSet<String> {
+    dex_pairs
+        .pairs
+        .iter()
+        .flatten()
+        .filter(|pair| pair.chain_id == chain.dexscreener_chain_id)
+        .map(|pair| pair.pair_address.to_lowercase())
+        .collect()
+}
+
+/// Builds the event for a transfer classified as `kind`, with the native side
+/// of the swap. The token price is left at zero for the caller to fill in.
+async fn trade_event(
+    explorer: &dyn BlockExplorer,
+    chain: &'static Chain,
+    transfer: &TokenTransferItem,
+    kind: TransferKind,
+    pairs: &HashSet<String>,
+) -> ExplorerResult<TradeEvent> {
+    let tx_info = match kind {
+        TransferKind::Buy => Some(explorer.tx_info(&transfer.tx_hash).await?),
+        _ => None,
+    };
+    let mut event = transfer_event(chain, transfer, kind)?;
+    event.native_amount =
+        native_amount(explorer, chain, transfer, kind, pairs, tx_info.as_ref()).await?;
+    event.gas_fee = tx_info
+        .map(|tx_info| TokenAmount::native(&tx_info.fee.value))
+        .transpose()?;
+    Ok(event)
+}
+
+fn transfer_event(
+    chain: &'static Chain,
+    transfer: &TokenTransferItem,
+    kind: TransferKind,
+) -> Result<TradeEvent, AmountError> {
+    Ok(TradeEvent {
+        kind,
+        chain,
+        token_address: transfer.token.address.clone(),
+        token_symbol: transfer.token.symbol.clone(),
+        tx_hash: transfer.tx_hash.clone(),
+        trader: match kind {
+            TransferKind::Sell => transfer.from.hash.clone(),
+            _ => transfer.to.hash.clone(),
+        },
+        token_price: 0.0,
+        token_amount: transfer.amount()?,
+        native_amount: None,
+        gas_fee: None,
+        total_supply: transfer.total_supply()?,
+    })
+}
+
+/// Posts the trade to one group, using that group's filters and style.
+async fn notify_trade(bot: &Bot, event: &TradeEvent, setting_opts: &SettingOpts) {
+    let Ok(group_chat_id) = setting_opts.group_chat_id.parse() else {
+        return;
+    };
+    let chat_id = ChatId(group_chat_id);
+
+    let Some(text) = render_trade(event, setting_opts) else {
+        return;
+    };
+    let (media_type, media_file_id) = match event.kind {
+        TransferKind::Sell => (
+            &setting_opts.sell_media_type,
+            &setting_opts.sell_media_file_id,
+        ),
+        _ => (&setting_opts.media_type, &setting_opts.media_file_id),
+    };
+
+    let media_file_id = media_file_id.clone().filter(|file_id| !file_id.is_empty());
+    let result = match media_file_id {
+        Some(file_id) if setting_opts.media_toggle && media_type == "photo" => bot
+            .send_photo(chat_id, InputFile::file_id(file_id))
+            .caption(text)
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await
+            .map(|_| ()),
+        Some(file_id) if setting_opts.media_toggle && media_type == "video" => bot
+            .send_video(chat_id, InputFile::file_id(file_id))
+            .caption(text)
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await
+            .map(|_| ()),
+        _ => bot
+            .send_message(chat_id, text)
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await
+            .map(|_| ()),
+    };
+    if let Err(e) = result {
+        error!(
+            "Failed to post trade {} to group {}: {}",
+            event.tx_hash, setting_opts.group_chat_id, e
+        );
+    }
+}
+
+/// The alert text for the group, or `None` if the group's settings filter
+/// the trade out.
+fn render_trade(event: &TradeEvent, setting_opts: &SettingOpts) -> Option<String> {
+    match event.kind {
+        TransferKind::Buy
+            if event.token_amount.value_at(event.token_price) > setting_opts.min_buy_amount =>
+        {
+            Some(buy_text(event, setting_opts))
+        }
+        TransferKind::Sell
+            if setting_opts.sell_alerts
+                && event.token_amount.value_at(event.token_price)
+                    > setting_opts.min_sell_amount =>
+        {
+            Some(sell_text(event, setting_opts))
+        }
+        _ => None,
+    }
+}
+
+/// Upper bound on the emojis of a bar, so a whale's buy still fits into a
+/// Telegram caption.
+const MAX_EMOJIS: usize = 50;
+
+/// One emoji plus one per `buy_step` dollars of the trade's value.
+fn emoji_bar(emoji: &str, value_usd: f64, buy_step: i32) -> String {
+    let steps = if buy_step > 0 {
+        value_usd / buy_step as f64
+    } else {
+        0.0
+    };
+    // The cast saturates, NaN and negative values count as no step
+    let emoji_count = (steps as usize).saturating_add(1).min(MAX_EMOJIS);
+    emoji.repeat(emoji_count)
+}
+
+fn buy_text(event: &TradeEvent, setting_opts: &SettingOpts) -> String {
+    let language = Language::of(setting_opts);
+    let template = match setting_opts.buy_template.as_deref().map(Template::parse) {
+        Some(Ok(template)) => template,
+        Some(Err(e)) => {
+            warn!(
+                "Invalid alert template for group {}, using the default: {}",
+                setting_opts.group_chat_id, e
+            );
+            Template::default_buy(language)
+        }
+        None => Template::default_buy(language),
+    };
+    template.render(|placeholder| {
+        placeholder_value(event, setting_opts, &setting_opts.emoji, placeholder)
+    })
+}
+
+fn sell_text(event: &TradeEvent, setting_opts: &SettingOpts) -> String {
+    Template::default_sell(Language::of(setting_opts)).render(|placeholder| {
+        placeholder_value(event, setting_opts, &setting_opts.sell_emoji, placeholder)
+    })
+}
+
+/// The unescaped value of a template placeholder for the trade, or `None`
+/// if the trade has none.
+fn placeholder_value(
+    event: &TradeEvent,
+    setting_opts: &SettingOpts,
+    emoji: &str,
+    placeholder: Placeholder,
+) -> Option<String> {
+    let locale = Language::of(setting_opts).number_locale();
+    let token_price = event.token_price;
+    let native_symbol = event.chain.native_symbol;
+    let value_usd = || format::compact(event.token_amount.value_at(token_price), locale);
+    let amount = || format::amount(event.token_amount.to_f64(), locale);
+
+    match placeholder {
+        Placeholder::EmojiBar => Some(emoji_bar(
+            emoji,
+            event.token_amount.value_at(token_price),
+            setting_opts.buy_step,
+        )),
+        Placeholder::SpentUsd | Placeholder::ValueUsd => Some(value_usd()),
+        Placeholder::SpentNative => Some(event.native_amount.map_or(String::new(), |native| {
+            format!(
+                " ({} {})",
+                format::amount(native.to_f64(), locale),
+                native_symbol
+            )
+        })),
+        Placeholder::GotAmount | Placeholder::Amount => Some(amount()),
+        Placeholder::Symbol => Some(event.token_symbol.clone()),
+        Placeholder::Gas => event.gas_fee.map(|gas_fee| {
+            format!(
+                "{} {}",
+                format::amount(gas_fee.to_f64(), locale),
+                native_symbol
+            )
+        }),
+        Placeholder::Price => Some(format::price(token_price, locale)),
+        Placeholder::Mcap => Some(format::compact(
+            event.total_supply.value_at(token_price),
+            locale,
+        )),
+        Placeholder::Buyer => Some(short_address(&event.trader)),
+        Placeholder::TxLink => Some(event.chain.tx_url(&event.tx_hash)),
+        Placeholder::ChartLink => Some(event.chain.chart_url(&event.token_address)),
+        Placeholder::DexLink => Some(event.chain.dex_url(&event.token_address)),
+        Placeholder::DexName => Some(event.chain.dex_name.to_string()),
+        Placeholder::TrendingLink => Some(event.chain.trending_url.unwrap_or_default().to_string()),
+        Placeholder::AdsLink => Some(event.chain.ads_url.unwrap_or_default().to_string()),
+        Placeholder::TgLink => Some(setting_opts.tg_link.clone()),
+        Placeholder::TwitterLink => Some(setting_opts.twitter_link.clone()),
+        Placeholder::WebsiteLink => Some(setting_opts.website_link.clone()),
+    }
+}
+
+/// `0x1234…abcd`
+pub fn short_address(address: &str) -> String {
+    match (
+        address.get(..6),
+        address.get(address.len().saturating_sub(4)..),
+    ) {
+        (Some(head), Some(tail)) if address.len() > 10 => format!("{}…{}", head, tail),
+        _ => address.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,927 @@
+//! Runs detection and rendering against a local stand-in for the Blockscout
+//! API that serves recorded responses from `tests/fixtures`, posting alerts
+//! to a stand-in for the Bot API.
+
+use std::collections::HashSet;
+
+use reqwest::Client;
+use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use async_trait::async_trait;
+
+use super::*;
+use crate::chain::find_chain;
+use crate::dex_pairs::{DexPair, PairResult};
+use crate::explorer::{BlockscoutClient, PageParams};
+use crate::price::{FallbackPriceProvider, PriceResult, TokenPrice};
+use crate::token_transfer::TokenTransfer;
+use crate::tx_info::TxInfo;
+
+const TOKEN: &str = "0x7f9a6a9e2b6c0b8d1c3e5f4a2b1c0d9e8f7a6b5c";
+const PAIR: &str = "0x3b1e2c9d8f7a6b5c4d3e2f1a0b9c8d7e6f5a4b3c";
+const TX_BUY: &str = "0xb0b1b0b1b0b1b0b1b0b1b0b1b0b1b0b1b0b1b0b1b0b1b0b1b0b1b0b1b0b1b0b1";
+const TX_BUY_OLDER: &str = "0xa11ca11ca11ca11ca11ca11ca11ca11ca11ca11ca11ca11ca11ca11ca11ca11c";
+const TX_SELL: &str = "0x5e115e115e115e115e115e115e115e115e115e115e115e115e115e115e115e11";
+
+/// The last transfer of page 2, i.e. what the previous poll ended on.
+const SEEN: TransferCursor = TransferCursor {
+    block_number: 100,
+    log_index: 2,
+};
+
+fn fixture(name: &str) -> ResponseTemplate {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let body = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    ResponseTemplate::new(200).set_body_raw(body, "application/json")
+}
+
+async fn explorer_stand_in() -> (MockServer, BlockscoutClient) {
+    let server = MockServer::start().await;
+    let transfers = format!("/api/v2/tokens/{}/transfers", TOKEN);
+
+    Mock::given(method("GET"))
+        .and(path(transfers.as_str()))
+        .and(query_param_is_missing("block_number"))
+        .respond_with(fixture("token_transfers_page1.json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(transfers.as_str()))
+        .and(query_param("block_number", "103"))
+        .and(query_param("index", "0"))
+        .respond_with(fixture("token_transfers_page2.json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/v2/transactions/{}", TX_BUY)))
+        .respond_with(fixture("tx_info_buy.json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/v2/transactions/{}", TX_BUY_OLDER)))
+        .respond_with(fixture("tx_info_buy_older.json"))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/api/v2/transactions/{}/token-transfers",
+            TX_BUY
+        )))
+        .respond_with(fixture("tx_token_transfers_buy.json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/api/v2/transactions/{}/token-transfers",
+            TX_SELL
+        )))
+        .respond_with(fixture("tx_token_transfers_sell.json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v2/stats"))
+        .respond_with(fixture("stats.json"))
+        .mount(&server)
+        .await;
+
+    let explorer = BlockscoutClient::new(Client::new(), format!("{}/api/v2", server.uri()));
+    (server, explorer)
+}
+
+fn pairs() -> HashSet<String> {
+    HashSet::from([PAIR.to_string()])
+}
+
+fn subscriber() -> SettingOpts {
+    SettingOpts {
+        group_chat_id: "-1001234567890".to_string(),
+        token_address: TOKEN.to_string(),
+        tg_link: "https://t.me/gorilla".to_string(),
+        sell_alerts: true,
+        ..SettingOpts::default()
+    }
+}
+
+#[tokio::test]
+async fn first_poll_reads_only_the_newest_page() {
+    let (_server, explorer) = explorer_stand_in().await;
+
+    let transfers = fetch_new_transfers(&explorer, TOKEN, None).await.unwrap();
+
+    let blocks: Vec<Option<u64>> = transfers.iter().map(|t| t.block_number).collect();
+    assert_eq!(blocks, vec![Some(103), Some(104), Some(105)]);
+}
+
+#[tokio::test]
+async fn follows_pages_back_to_the_cursor_oldest_first() {
+    let (_server, explorer) = explorer_stand_in().await;
+
+    let transfers = fetch_new_transfers(&explorer, TOKEN, Some(SEEN))
+        .await
+        .unwrap();
+
+    let cursors: Vec<(u64, u64)> = transfers
+        .iter()
+        .map(|t| t.cursor().unwrap())
+        .map(|cursor| (cursor.block_number, cursor.log_index))
+        .collect();
+    assert_eq!(cursors, vec![(102, 5), (103, 0), (104, 1), (105, 3)]);
+}
+
+#[tokio::test]
+async fn nothing_new_after_the_newest_transfer() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let newest = TransferCursor {
+        block_number: 105,
+        log_index: 3,
+    };
+
+    let transfers = fetch_new_transfers(&explorer, TOKEN, Some(newest))
+        .await
+        .unwrap();
+
+    assert!(transfers.is_empty());
+}
+
+#[tokio::test]
+async fn transfers_without_a_position_are_explorer_errors() {
+    let page = std::fs::read_to_string(format!(
+        "{}/tests/fixtures/token_transfers_page1.json",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap();
+    for broken in [
+        page.replacen("\"block_number\": 105,", "", 1),
+        page.replacen("\"log_index\": \"3\"", "\"log_index\": \"\"", 1),
+    ] {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(broken, "application/json"))
+            .mount(&server)
+            .await;
+        let explorer = BlockscoutClient::new(Client::new(), format!("{}/api/v2", server.uri()));
+
+        assert!(fetch_new_transfers(&explorer, TOKEN, None).await.is_err());
+        assert!(fetch_new_transfers(&explorer, TOKEN, Some(SEEN))
+            .await
+            .is_err());
+    }
+}
+
+/// A pair source listing `PAIR` as the token's only ApeChain pair.
+struct Listed;
+
+#[async_trait]
+impl PairSource for Listed {
+    async fn dex_pairs(&self, _: &str) -> PairResult<DexPairs> {
+        Ok(DexPairs {
+            pairs: Some(vec![DexPair {
+                chain_id: "apechain".to_string(),
+                pair_address: PAIR.to_string(),
+                ..DexPair::default()
+            }]),
+        })
+    }
+}
+
+struct FixedPrice(f64);
+
+#[async_trait]
+impl PriceProvider for FixedPrice {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    async fn token_price(&self, _: &Chain, _: &str) -> PriceResult<TokenPrice> {
+        Ok(TokenPrice::live(self.0))
+    }
+}
+
+/// A stand-in for the Bot API that accepts every message.
+async fn telegram_stand_in() -> (MockServer, Bot) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "ok": true,
+            "result": {
+                "message_id": 1,
+                "date": 0,
+                "chat": {"id": -1001234567890i64, "type": "supergroup", "title": "Gorilla"},
+                "text": "alert"
+            }
+        })))
+        .mount(&server)
+        .await;
+    let bot = Bot::new("0:test").set_api_url(server.uri().parse().unwrap());
+    (server, bot)
+}
+
+/// The chat and text of every message posted so far.
+async fn posted(telegram: &MockServer) -> Vec<(i64, String)> {
+    telegram
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path().ends_with("/SendMessage"))
+        .map(|request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            (
+                body["chat_id"].as_i64().unwrap(),
+                body["text"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+/// The poll of `TOKEN` on ApeChain as the registry builds it, for one
+/// subscribed group and at $0.002 a token.
+fn token_poll(bot: Bot, explorer: BlockscoutClient) -> TokenPoll {
+    let explorer: Arc<dyn BlockExplorer> = Arc::new(explorer);
+    let watchers = WatcherRegistry::new(
+        bot,
+        Arc::new(FixedPrice(0.002)),
+        SwapPricing::Off,
+        Box::new(move |_| explorer.clone()),
+        Arc::new(Listed),
+    );
+    let subscribers = HashMap::from([(subscriber().group_chat_id, subscriber())]);
+    watchers.token_poll(
+        find_chain("apechain").unwrap(),
+        TOKEN,
+        Arc::new(RwLock::new(subscribers)),
+    )
+}
+
+/// Builds the event the poller would post, at a fixed token price.
+async fn priced_event(
+    explorer: &BlockscoutClient,
+    transfer: &TokenTransferItem,
+    kind: TransferKind,
+    token_price: f64,
+) -> TradeEvent {
+    let chain = find_chain("apechain").unwrap();
+    let mut event = trade_event(explorer, chain, transfer, kind, &pairs())
+        .await
+        .unwrap();
+    event.token_price = token_price;
+    event
+}
+
+#[tokio::test]
+async fn a_poll_posts_every_new_buy_and_sell() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let (telegram, bot) = telegram_stand_in().await;
+    let mut poll = token_poll(bot, explorer);
+    poll.cursor = Some(SEEN);
+
+    poll.poll().await;
+
+    let alerts = posted(&telegram).await;
+    assert!(alerts.iter().all(|(chat_id, _)| *chat_id == -1001234567890));
+    let alerts: Vec<&str> = alerts.iter().map(|(_, text)| text.as_str()).collect();
+    let tx_links: Vec<String> = [TX_BUY_OLDER, TX_BUY, TX_SELL]
+        .iter()
+        .map(|tx_hash| format!("https://apescan.io/tx/{}", tx_hash))
+        .collect();
+    assert_eq!(alerts.len(), 3, "{:?}", alerts);
+    for (alert, tx_link) in alerts.iter().zip(&tx_links) {
+        assert!(alert.contains(tx_link.as_str()), "{}", alert);
+    }
+    assert_eq!(poll.cursor, at(105, 3));
+
+    // $40 at the default step of $30
+    let older_buy = alerts[0];
+    assert!(older_buy.starts_with("💎💎\n"), "{}", older_buy);
+    assert!(
+        older_buy.contains(
+            "💲 Spent: $40 (15 APE)\n\
+             💰 Got: 20,000 $GORILLA\n\
+             ⛽ Gas: 0.003611 APE\n"
+        ),
+        "{}",
+        older_buy
+    );
+
+    let buy = alerts[1];
+    // $3K would be 101 emojis
+    assert!(
+        buy.starts_with(&format!("{}\n", "💎".repeat(MAX_EMOJIS))),
+        "{}",
+        buy
+    );
+    assert!(
+        buy.contains(
+            "💲 Spent: $3K (1,250 APE)\n\
+             💰 Got: 1.5M $GORILLA\n\
+             ⛽ Gas: 0.003611 APE\n"
+        ),
+        "{}",
+        buy
+    );
+    assert!(buy.contains("📊 Marketcap: $2M\n"), "{}", buy);
+    assert!(buy.contains(&format!("https://dexscreener.com/apechain/{}", TOKEN)));
+    assert!(buy.contains("<a href=\"https://t.me/gorilla\">TG</a>"));
+    assert!(buy.contains("<a href=\"https://t.me/Apechain_Trending_Bot\">"));
+
+    let sell = alerts[2];
+    assert!(
+        sell.contains("🔻 Sold: 250,000 $GORILLA\n💲 Value: $500\n"),
+        "{}",
+        sell
+    );
+
+    // Nothing is posted twice
+    poll.poll().await;
+    assert_eq!(posted(&telegram).await.len(), 3);
+}
+
+fn at(block_number: u64, log_index: u64) -> Option<TransferCursor> {
+    Some(TransferCursor {
+        block_number,
+        log_index,
+    })
+}
+
+/// Makes the explorer fail to look up `TX_BUY`, `times` times or for good.
+async fn break_tx_buy(server: &MockServer, times: Option<u64>) {
+    let mock = Mock::given(method("GET"))
+        .and(path(format!("/api/v2/transactions/{}", TX_BUY)))
+        .respond_with(ResponseTemplate::new(502))
+        .with_priority(1);
+    match times {
+        Some(times) => mock.up_to_n_times(times).mount(server).await,
+        None => mock.mount(server).await,
+    }
+}
+
+#[tokio::test]
+async fn the_first_poll_only_marks_where_to_start() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let (telegram, bot) = telegram_stand_in().await;
+    let mut poll = token_poll(bot, explorer);
+
+    poll.poll().await;
+    assert_eq!(poll.cursor, at(105, 3));
+    poll.poll().await;
+
+    assert!(posted(&telegram).await.is_empty());
+}
+
+#[tokio::test]
+async fn a_new_token_reports_its_first_trades() {
+    let (server, explorer) = explorer_stand_in().await;
+    let (telegram, bot) = telegram_stand_in().await;
+    let transfers = format!("/api/v2/tokens/{}/transfers", TOKEN);
+    Mock::given(method("GET"))
+        .and(path(transfers.as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "items": [],
+            "next_page_params": null
+        })))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    // Then the token's first three transfers land
+    let page_path = format!(
+        "{}/tests/fixtures/token_transfers_page1.json",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let mut first_page: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(page_path).unwrap()).unwrap();
+    first_page["next_page_params"] = serde_json::Value::Null;
+    Mock::given(method("GET"))
+        .and(path(transfers.as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(first_page))
+        .with_priority(2)
+        .mount(&server)
+        .await;
+    let mut poll = token_poll(bot, explorer);
+
+    poll.poll().await;
+    assert_eq!(poll.cursor, Some(TransferCursor::START));
+    poll.poll().await;
+
+    let alerts = posted(&telegram).await;
+    assert_eq!(alerts.len(), 2, "{:?}", alerts);
+    assert!(alerts[0].1.contains(TX_BUY), "{}", alerts[0].1);
+    assert!(alerts[1].1.contains(TX_SELL), "{}", alerts[1].1);
+    assert_eq!(poll.cursor, at(105, 3));
+}
+
+#[tokio::test]
+async fn a_failed_trade_is_retried_on_the_next_polls() {
+    let (server, explorer) = explorer_stand_in().await;
+    let (telegram, bot) = telegram_stand_in().await;
+    break_tx_buy(&server, Some(MAX_TRADE_ATTEMPTS as u64 - 1)).await;
+    let mut poll = token_poll(bot, explorer);
+    poll.cursor = Some(SEEN);
+
+    // The trades before the failing one are posted, the cursor stops in
+    // front of it
+    poll.poll().await;
+    assert_eq!(posted(&telegram).await.len(), 1);
+    assert_eq!(poll.cursor, at(103, 0));
+    poll.poll().await;
+    assert_eq!(posted(&telegram).await.len(), 1);
+    assert_eq!(poll.cursor, at(103, 0));
+
+    poll.poll().await;
+    let alerts = posted(&telegram).await;
+    assert_eq!(alerts.len(), 3, "{:?}", alerts);
+    assert!(alerts[1].1.contains(TX_BUY), "{}", alerts[1].1);
+    assert!(alerts[2].1.contains(TX_SELL), "{}", alerts[2].1);
+    assert_eq!(poll.cursor, at(105, 3));
+    assert_eq!(poll.failed_attempts, 0);
+}
+
+#[tokio::test]
+async fn a_trade_that_keeps_failing_is_skipped() {
+    let (server, explorer) = explorer_stand_in().await;
+    let (telegram, bot) = telegram_stand_in().await;
+    break_tx_buy(&server, None).await;
+    let mut poll = token_poll(bot, explorer);
+    poll.cursor = Some(SEEN);
+
+    for _ in 1..MAX_TRADE_ATTEMPTS {
+        poll.poll().await;
+        assert_eq!(poll.cursor, at(103, 0));
+    }
+    poll.poll().await;
+
+    let alerts = posted(&telegram).await;
+    assert_eq!(alerts.len(), 2, "{:?}", alerts);
+    assert!(alerts[0].1.contains(TX_BUY_OLDER), "{}", alerts[0].1);
+    assert!(alerts[1].1.contains(TX_SELL), "{}", alerts[1].1);
+    assert_eq!(poll.cursor, at(105, 3));
+    assert_eq!(poll.failed_attempts, 0);
+}
+
+#[tokio::test]
+async fn a_token_without_pairs_is_looked_up_again_sooner() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let mut poll = token_poll(Bot::new("0:test"), explorer);
+    assert!(poll.pairs_due());
+
+    // Two minutes after DexScreener listed no pair
+    poll.pairs_refreshed_at = Instant::now().checked_sub(Duration::from_secs(120));
+    assert!(poll.pairs_due());
+    poll.refresh_pairs().await;
+    assert_eq!(poll.pairs, pairs());
+    assert!(!poll.pairs_due());
+
+    poll.pairs_refreshed_at = Instant::now().checked_sub(Duration::from_secs(120));
+    assert!(!poll.pairs_due());
+}
+
+#[tokio::test]
+async fn other_chains_have_no_trending_or_ads_links() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let transfers = fetch_new_transfers(&explorer, TOKEN, Some(SEEN))
+        .await
+        .unwrap();
+
+    let event = TradeEvent {
+        chain: find_chain("base").unwrap(),
+        ..priced_event(&explorer, &transfers[2], TransferKind::Buy, 0.002).await
+    };
+    let buy = render_trade(&event, &subscriber()).unwrap();
+    assert!(buy.contains("\">Uniswap</a>\n"), "{}", buy);
+    assert!(!buy.contains("Apechain"), "{}", buy);
+}
+
+#[tokio::test]
+async fn spent_is_the_native_amount_and_gas_is_separate() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let transfers = fetch_new_transfers(&explorer, TOKEN, Some(SEEN))
+        .await
+        .unwrap();
+
+    // Paid in APE: the transaction's value
+    let event = priced_event(&explorer, &transfers[0], TransferKind::Buy, 0.002).await;
+    assert_eq!(event.token_amount.to_string(), "20000");
+    assert_eq!(event.native_amount.unwrap().to_string(), "15");
+    assert_eq!(event.gas_fee.unwrap().to_string(), "0.0036105297");
+
+    // Paid in WAPE: the wrapped leg into the pair
+    let event = priced_event(&explorer, &transfers[2], TransferKind::Buy, 0.002).await;
+    assert_eq!(event.token_amount.to_string(), "1500000");
+    assert_eq!(event.native_amount.unwrap().to_string(), "1250");
+    assert_eq!(event.gas_fee.unwrap().to_string(), "0.0036105297");
+
+    // Sold for WAPE out of the pair; the seller's gas is not looked up
+    let event = priced_event(&explorer, &transfers[3], TransferKind::Sell, 0.002).await;
+    assert_eq!(event.token_amount.to_string(), "250000");
+    assert_eq!(event.native_amount.unwrap().to_string(), "300");
+    assert_eq!(event.gas_fee, None);
+}
+
+#[tokio::test]
+async fn amounts_are_exact_and_unparsable_ones_are_errors() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let mut transfers = fetch_new_transfers(&explorer, TOKEN, Some(SEEN))
+        .await
+        .unwrap();
+    let chain = find_chain("apechain").unwrap();
+
+    // 10^27 raw units do not fit an f64 mantissa
+    let event = transfer_event(chain, &transfers[0], TransferKind::Buy).unwrap();
+    assert_eq!(event.total_supply.to_string(), "1000000000");
+
+    transfers[0].total.value = "2e22".to_string();
+    assert!(transfer_event(chain, &transfers[0], TransferKind::Buy).is_err());
+    transfers[0].total.value = "20000000000000000000000".to_string();
+    transfers[0].token.decimals = "eighteen".to_string();
+    assert!(transfer_event(chain, &transfers[0], TransferKind::Buy).is_err());
+}
+
+#[tokio::test]
+async fn group_filters_are_applied_when_rendering() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let transfers = fetch_new_transfers(&explorer, TOKEN, Some(SEEN))
+        .await
+        .unwrap();
+
+    // 20000 tokens at $0.002 are worth $40
+    let event = priced_event(&explorer, &transfers[0], TransferKind::Buy, 0.002).await;
+    let above = SettingOpts {
+        min_buy_amount: 50.0,
+        ..subscriber()
+    };
+    assert!(render_trade(&event, &above).is_none());
+    let below = SettingOpts {
+        min_buy_amount: 30.0,
+        ..subscriber()
+    };
+    assert!(render_trade(&event, &below).is_some());
+
+    let event = priced_event(&explorer, &transfers[3], TransferKind::Sell, 0.002).await;
+    let sells_off = SettingOpts {
+        sell_alerts: false,
+        ..subscriber()
+    };
+    assert!(render_trade(&event, &sells_off).is_none());
+}
+
+#[test]
+fn emoji_bars_grow_with_the_value_up_to_a_cap() {
+    assert_eq!(emoji_bar("🦍", 0.0, 30), "🦍");
+    assert_eq!(emoji_bar("🦍", 29.9, 30), "🦍");
+    assert_eq!(emoji_bar("🦍", 30.0, 30), "🦍🦍");
+    assert_eq!(emoji_bar("🦍", 60.0, 30), "🦍🦍🦍");
+    assert_eq!(emoji_bar("🦍", 1e11, 30), "🦍".repeat(MAX_EMOJIS));
+    assert_eq!(emoji_bar("🦍", f64::INFINITY, 1), "🦍".repeat(MAX_EMOJIS));
+    assert_eq!(emoji_bar("🦍", f64::NAN, 30), "🦍");
+    // Steps saved before they were checked
+    assert_eq!(emoji_bar("🦍", 1e11, 0), "🦍");
+    assert_eq!(emoji_bar("🦍", 1e11, -5), "🦍");
+}
+
+#[tokio::test]
+async fn buys_are_rendered_with_the_group_template() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let transfers = fetch_new_transfers(&explorer, TOKEN, Some(SEEN))
+        .await
+        .unwrap();
+    let custom = SettingOpts {
+        buy_template: Some(
+            "{buyer} bought {got_amount} ${symbol} for ${spent_usd}\n⛽ {gas}\n{{{price}}}"
+                .to_string(),
+        ),
+        ..subscriber()
+    };
+
+    let event = priced_event(&explorer, &transfers[0], TransferKind::Buy, 0.002).await;
+    assert_eq!(
+        render_trade(&event, &custom).unwrap(),
+        "0xa11c…0001 bought 20,000 $GORILLA for $40\n⛽ 0.003611 APE\n{0.002}"
+    );
+
+    // Without a gas fee the gas line is left out
+    let event = TradeEvent {
+        gas_fee: None,
+        ..event
+    };
+    assert_eq!(
+        render_trade(&event, &custom).unwrap(),
+        "0xa11c…0001 bought 20,000 $GORILLA for $40\n{0.002}"
+    );
+
+    // A template broken in the database falls back to the default layout
+    let broken = SettingOpts {
+        buy_template: Some("{volume}".to_string()),
+        ..subscriber()
+    };
+    assert!(render_trade(&event, &broken)
+        .unwrap()
+        .contains("💰 Got: 20,000 $GORILLA\n"));
+}
+
+#[tokio::test]
+async fn alerts_use_the_group_language() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let transfers = fetch_new_transfers(&explorer, TOKEN, Some(SEEN))
+        .await
+        .unwrap();
+    let german = SettingOpts {
+        language: "de".to_string(),
+        sell_alerts: true,
+        ..subscriber()
+    };
+
+    let event = priced_event(&explorer, &transfers[0], TransferKind::Buy, 0.002).await;
+    let buy = render_trade(&event, &german).unwrap();
+    assert!(
+        buy.contains(
+            "💲 Ausgegeben: $40 (15 APE)\n💰 Erhalten: 20.000 $GORILLA\n⛽ Gas: 0,003611 APE\n"
+        ),
+        "{}",
+        buy
+    );
+    assert!(buy.contains("📊 Marktkapitalisierung: $2M\n"), "{}", buy);
+
+    let event = priced_event(&explorer, &transfers[3], TransferKind::Sell, 0.002).await;
+    let sell = render_trade(&event, &german).unwrap();
+    assert!(
+        sell.contains("🔻 Verkauft: 250.000 $GORILLA\n💲 Wert: $500"),
+        "{}",
+        sell
+    );
+}
+
+#[tokio::test]
+async fn untrusted_names_and_links_are_made_html_safe() {
+    let (_server, explorer) = explorer_stand_in().await;
+    let transfers = fetch_new_transfers(&explorer, TOKEN, Some(SEEN))
+        .await
+        .unwrap();
+    let mut event = priced_event(&explorer, &transfers[3], TransferKind::Sell, 0.002).await;
+    event.token_symbol = "<b>G&G".to_string();
+    let group = SettingOpts {
+        tg_link: "\"><script>".to_string(),
+        twitter_link: String::new(),
+        website_link: "https://gorilla.example/?a=1&b=2".to_string(),
+        ..subscriber()
+    };
+
+    let sell = render_trade(&event, &group).unwrap();
+    assert!(
+        sell.contains("🔻 Sold: 250,000 $&lt;b&gt;G&amp;G\n"),
+        "{}",
+        sell
+    );
+    assert!(
+        sell.ends_with(&format!(
+            "<a href=\"https://apescan.io/tx/{}\">TX</a> | \
+             <a href=\"https://dexscreener.com/apechain/{}\">Chart</a> | \
+             <a href=\"https://gorilla.example/?a=1&amp;b=2\">Website</a>",
+            TX_SELL, TOKEN
+        )),
+        "{}",
+        sell
+    );
+    assert!(!sell.contains("href=\"\""));
+    assert!(!sell.contains("<script>"));
+}
+
+#[tokio::test]
+async fn prices_trades_from_their_swap_legs() {
+    let (server, explorer) = explorer_stand_in().await;
+    let chain = find_chain("apechain").unwrap();
+    let native_usd = NativeUsdCache::new();
+    let transfers = fetch_new_transfers(&explorer, TOKEN, Some(SEEN))
+        .await
+        .unwrap();
+
+    // 1250 WAPE paid into the pair for 1.5M tokens, at $0.85 per APE
+    let event = priced_event(&explorer, &transfers[2], TransferKind::Buy, 0.0).await;
+    let price = swap_price(
+        &explorer,
+        chain,
+        event.native_amount,
+        event.token_amount,
+        &native_usd,
+    )
+    .await
+    .unwrap();
+    assert!(
+        (price - 1250.0 / 1_500_000.0 * 0.85).abs() < 1e-12,
+        "{}",
+        price
+    );
+
+    // 15 APE sent as the transaction value for 20000 tokens
+    let event = priced_event(&explorer, &transfers[0], TransferKind::Buy, 0.0).await;
+    let price = swap_price(
+        &explorer,
+        chain,
+        event.native_amount,
+        event.token_amount,
+        &native_usd,
+    )
+    .await
+    .unwrap();
+    assert!((price - 15.0 / 20_000.0 * 0.85).abs() < 1e-12, "{}", price);
+
+    // The native/USD rate was read once and reused
+    let requests = server.received_requests().await.unwrap();
+    let stats_reads = requests
+        .iter()
+        .filter(|request| request.url.path() == "/api/v2/stats")
+        .count();
+    assert_eq!(stats_reads, 1);
+
+    assert!(
+        swap_price(&explorer, chain, None, event.token_amount, &native_usd)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn explorer_errors_are_reported() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(502))
+        .mount(&server)
+        .await;
+    let explorer = BlockscoutClient::new(Client::new(), format!("{}/api/v2", server.uri()));
+
+    assert!(fetch_new_transfers(&explorer, TOKEN, None).await.is_err());
+    assert!(explorer.tx_info(TX_BUY).await.is_err());
+}
+
+/// An explorer on which nothing ever happens.
+struct Quiet;
+
+#[async_trait]
+impl BlockExplorer for Quiet {
+    async fn token_transfers(
+        &self,
+        _: &str,
+        _: Option<&PageParams>,
+    ) -> ExplorerResult<TokenTransfer> {
+        Ok(Default::default())
+    }
+
+    async fn tx_info(&self, _: &str) -> ExplorerResult<TxInfo> {
+        Err("no transactions".into())
+    }
+
+    async fn tx_token_transfers(&self, _: &str) -> ExplorerResult<TokenTransfer> {
+        Err("no transactions".into())
+    }
+
+    async fn native_usd_price(&self) -> ExplorerResult<f64> {
+        Err("no price".into())
+    }
+}
+
+/// A registry whose pollers never see a trade, the tests only look at who is
+/// subscribed. Nothing leaves the process.
+fn registry() -> WatcherRegistry {
+    WatcherRegistry::new(
+        Bot::new("0:test"),
+        Arc::new(FallbackPriceProvider::new(Vec::new(), Duration::ZERO)),
+        SwapPricing::Off,
+        Box::new(|_| Arc::new(Quiet)),
+        Arc::new(Listed),
+    )
+}
+
+fn tracking(group_chat_id: &str, token_address: &str) -> SettingOpts {
+    SettingOpts {
+        group_chat_id: group_chat_id.to_string(),
+        chain_id: "apechain".to_string(),
+        token_address: token_address.to_string(),
+        ..SettingOpts::default()
+    }
+}
+
+async fn watched(watchers: &WatcherRegistry) -> Vec<(String, String)> {
+    let mut watched: Vec<(String, String)> = watchers
+        .list()
+        .await
+        .into_iter()
+        .map(|key| (key.group_chat_id, key.token_address))
+        .collect();
+    watched.sort();
+    watched
+}
+
+#[tokio::test]
+async fn groups_on_the_same_token_share_one_poller() {
+    let watchers = registry();
+
+    assert!(watchers.start(tracking("-10", TOKEN)).await.unwrap());
+    assert!(!watchers.start(tracking("-10", TOKEN)).await.unwrap());
+    // Addresses differing in case are the same token
+    let checksummed = format!("0x{}", TOKEN[2..].to_uppercase());
+    assert!(!watchers.start(tracking("-10", &checksummed)).await.unwrap());
+    assert!(watchers.start(tracking("-20", TOKEN)).await.unwrap());
+    assert!(watchers.start(tracking("-10", PAIR)).await.unwrap());
+
+    assert_eq!(watchers.pollers.read().await.len(), 2);
+    assert_eq!(
+        watched(&watchers).await,
+        vec![
+            ("-10".to_string(), PAIR.to_string()),
+            ("-10".to_string(), TOKEN.to_string()),
+            ("-20".to_string(), TOKEN.to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn tells_whether_the_token_has_a_pair_on_its_chain() {
+    let watchers = registry();
+
+    assert!(watchers.has_dex_pairs("apechain", TOKEN).await);
+    assert!(!watchers.has_dex_pairs("base", TOKEN).await);
+    assert!(!watchers.has_dex_pairs("dogechain", TOKEN).await);
+}
+
+#[tokio::test]
+async fn rejects_what_it_cannot_watch() {
+    let watchers = registry();
+
+    assert!(watchers.start(tracking("group", TOKEN)).await.is_err());
+    assert!(watchers.start(tracking("-10", "0x123")).await.is_err());
+    let unknown_chain = SettingOpts {
+        chain_id: "dogechain".to_string(),
+        ..tracking("-10", TOKEN)
+    };
+    assert!(watchers.start(unknown_chain).await.is_err());
+    assert!(watchers.list().await.is_empty());
+}
+
+#[tokio::test]
+async fn the_poller_stops_with_its_last_group() {
+    let watchers = registry();
+    watchers.start(tracking("-10", TOKEN)).await.unwrap();
+    watchers.start(tracking("-20", TOKEN)).await.unwrap();
+
+    assert!(watchers.stop("-10", "apechain", TOKEN).await);
+    assert!(!watchers.stop("-10", "apechain", TOKEN).await);
+    assert_eq!(watchers.pollers.read().await.len(), 1);
+
+    assert!(
+        watchers
+            .stop("-20", "apechain", &TOKEN.to_uppercase())
+            .await
+    );
+    assert!(watchers.pollers.read().await.is_empty());
+    assert!(watchers.list().await.is_empty());
+    assert!(!watchers.stop("-20", "apechain", TOKEN).await);
+}
+
+#[tokio::test]
+async fn restart_swaps_the_settings_in_place() {
+    let watchers = registry();
+
+    // Not subscribed yet: restarting subscribes
+    watchers.restart(tracking("-10", TOKEN)).await.unwrap();
+    watchers.start(tracking("-20", TOKEN)).await.unwrap();
+    let rocket = SettingOpts {
+        emoji: "🚀".to_string(),
+        ..tracking("-10", TOKEN)
+    };
+    watchers.restart(rocket).await.unwrap();
+
+    let pollers = watchers.pollers.read().await;
+    assert_eq!(pollers.len(), 1);
+    let subscribers = pollers.values().next().unwrap().subscribers.read().await;
+    assert_eq!(subscribers["-10"].emoji, "🚀");
+    assert_eq!(subscribers["-20"].emoji, SettingOpts::default().emoji);
+}
+
+#[tokio::test]
+async fn a_finished_poller_is_replaced_with_all_its_groups() {
+    let watchers = registry();
+    watchers.start(tracking("-10", TOKEN)).await.unwrap();
+    watchers.start(tracking("-20", TOKEN)).await.unwrap();
+
+    // As if the poller had panicked
+    let handle = {
+        let pollers = watchers.pollers.read().await;
+        let poller = pollers.values().next().unwrap();
+        poller.handle.abort();
+        poller.handle.abort_handle()
+    };
+    while !handle.is_finished() {
+        tokio::task::yield_now().await;
+    }
+    assert!(watchers.list().await.is_empty());
+
+    assert!(watchers.start(tracking("-30", TOKEN)).await.unwrap());
+    let groups: Vec<String> = watched(&watchers)
+        .await
+        .into_iter()
+        .map(|(group_chat_id, _)| group_chat_id)
+        .collect();
+    assert_eq!(groups, ["-10", "-20", "-30"]);
+    assert!(!watchers.start(tracking("-10", TOKEN)).await.unwrap());
+}